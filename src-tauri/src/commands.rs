@@ -1,8 +1,23 @@
 use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet, VecDeque};
 use std::fs::{self, File, OpenOptions};
-use std::io::{Read, Seek, SeekFrom, Write};
-use std::path::PathBuf;
-use chrono::Utc;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Datelike, NaiveDateTime, Utc};
+use flate2::read::{GzDecoder, MultiGzDecoder};
+use notify::event::ModifyKind;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use regex::{Regex, RegexBuilder};
+use tauri::{AppHandle, Emitter, Manager, State, Window};
+
+use crate::state::{AppState, Config, LineIndex};
 
 // Read at most 2MB from end of file - enough for ~10K+ lines
 // Frontend only displays last 2000 lines anyway
@@ -10,49 +25,450 @@ const MAX_READ_SIZE: u64 = 2 * 1024 * 1024;
 const MAX_RECENT: usize = 20;
 
 /// Response for readFile command
-#[derive(Serialize)]
+#[derive(Serialize, Default)]
 pub struct FileResult {
     pub success: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub content: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub path: Option<String>,
+    /// File name only
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    /// Name to show the user: the file name, or a path relative to a base when
+    /// names alone are ambiguous. Empty on failure
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub display_name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub size: Option<u64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub prev_size: Option<u64>,
+    /// Byte offset this read started from
+    pub read_from_offset: u64,
+    /// Offset to pass to the next polling read (the size seen by this read)
+    pub next_offset: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mtime: Option<i64>,
+    /// mtime as an RFC 3339 string in UTC
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mtime_iso: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub truncated: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefix_pattern_used: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines_with_stripped_prefix: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines_returned: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_lines: Option<usize>,
+    /// 0-indexed line within content that holds a requested anchor (e.g. a bookmark)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor_line: Option<usize>,
+    /// U+FFFD characters in the content, mostly from invalid UTF-8 replaced while decoding
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replacement_char_count: Option<u32>,
+    /// On success, a warning about content that isn't valid UTF-8
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    /// Set alongside error for failures the frontend handles specially
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<CommandError>,
+}
+
+/// Machine-readable failure codes, serialized as the variant name
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommandError {
+    /// The file existed when it was stat'ed but was gone by the time it was opened
+    FileDisappearedBetweenStatAndOpen,
+    /// The file is over the size limit for the operation
+    FileTooLarge,
+}
+
+impl CommandError {
+    /// Message shown to the user for this error
+    pub fn message(self) -> &'static str {
+        match self {
+            CommandError::FileDisappearedBetweenStatAndOpen => "The file was deleted while reading",
+            CommandError::FileTooLarge => "The file is too large",
+        }
+    }
+}
+
+impl FileResult {
+    /// Failed result carrying only an error message
+    fn error(message: &str) -> Self {
+        FileResult {
+            success: false,
+            error: Some(message.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Failed result carrying an error code and its message
+    fn error_code(code: CommandError) -> Self {
+        FileResult {
+            error_code: Some(code),
+            ..FileResult::error(code.message())
+        }
+    }
 }
 
 /// Recent file entry
-#[derive(Serialize, Deserialize, Clone)]
+/// Serialized by hand for the frontend so that name reflects the alias when one is set;
+/// recent.json is written through StoredRecentFile instead
+#[derive(Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct RecentFile {
     pub path: String,
     pub name: String,
     pub last_opened: i64,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub mtime: Option<i64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub size: Option<u64>,
     #[serde(default)]
     pub exists: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub open_count: u32,
+    #[serde(default)]
+    pub is_pinned: bool,
+    #[serde(default)]
+    pub alias: Option<String>,
+    /// False when mtime/size are stale values from recent.json
+    #[serde(default)]
+    pub metadata_fresh: bool,
+    #[serde(default)]
+    pub group: Option<String>,
+    /// Free-text note on why the file matters
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// Time spent in recorded file sessions, filled in from sessions_log.json when listing
+    #[serde(skip_deserializing)]
+    pub total_open_seconds: Option<u64>,
+}
+
+impl RecentFile {
+    /// The alias if one is set, otherwise the original filename
+    fn display_name(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.name)
+    }
+}
+
+impl Serialize for RecentFile {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("RecentFile", 16)?;
+        state.serialize_field("path", &self.path)?;
+        state.serialize_field("name", self.display_name())?;
+        state.serialize_field("lastOpened", &self.last_opened)?;
+        match self.mtime {
+            Some(mtime) => {
+                state.serialize_field("mtime", &mtime)?;
+                match mtime_iso(mtime) {
+                    Some(iso) => state.serialize_field("mtimeIso", &iso)?,
+                    None => state.skip_field("mtimeIso")?,
+                }
+            }
+            None => {
+                state.skip_field("mtime")?;
+                state.skip_field("mtimeIso")?;
+            }
+        }
+        match self.size {
+            Some(size) => state.serialize_field("size", &size)?,
+            None => state.skip_field("size")?,
+        }
+        state.serialize_field("exists", &self.exists)?;
+        state.serialize_field("tags", &self.tags)?;
+        state.serialize_field("openCount", &self.open_count)?;
+        state.serialize_field("isPinned", &self.is_pinned)?;
+        match &self.alias {
+            Some(alias) => state.serialize_field("alias", alias)?,
+            None => state.skip_field("alias")?,
+        }
+        state.serialize_field("metadataFresh", &self.metadata_fresh)?;
+        match &self.group {
+            Some(group) => state.serialize_field("group", group)?,
+            None => state.skip_field("group")?,
+        }
+        match &self.notes {
+            Some(notes) => state.serialize_field("notes", notes)?,
+            None => state.skip_field("notes")?,
+        }
+        match self.total_open_seconds {
+            Some(seconds) => state.serialize_field("totalOpenSeconds", &seconds)?,
+            None => state.skip_field("totalOpenSeconds")?,
+        }
+        state.end()
+    }
+}
+
+/// A RecentFile as written to recent.json and exports: the real filename and alias are
+/// kept apart, and values derived for the frontend are left out
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredRecentFile<'a> {
+    path: &'a str,
+    name: &'a str,
+    last_opened: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mtime: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<u64>,
+    exists: bool,
+    tags: &'a [String],
+    open_count: u32,
+    is_pinned: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    alias: Option<&'a str>,
+    metadata_fresh: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    group: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    notes: Option<&'a str>,
+}
+
+impl<'a> From<&'a RecentFile> for StoredRecentFile<'a> {
+    fn from(f: &'a RecentFile) -> Self {
+        StoredRecentFile {
+            path: &f.path,
+            name: &f.name,
+            last_opened: f.last_opened,
+            mtime: f.mtime,
+            size: f.size,
+            exists: f.exists,
+            tags: &f.tags,
+            open_count: f.open_count,
+            is_pinned: f.is_pinned,
+            alias: f.alias.as_deref(),
+            metadata_fresh: f.metadata_fresh,
+            group: f.group.as_deref(),
+            notes: f.notes.as_deref(),
+        }
+    }
+}
+
+/// Get the directory holding Mocha's settings and lists: $MOCHA_CONFIG_DIR if set, else ~/.mocha
+pub fn get_config_dir() -> Option<PathBuf> {
+    match std::env::var_os("MOCHA_CONFIG_DIR") {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => dirs::home_dir().map(|home| home.join(".mocha")),
+    }
 }
 
-/// Get the path to ~/.mocha/recent.json
+/// Get the path to recent.json in the config directory ($MOCHA_CONFIG_DIR or ~/.mocha)
 fn get_recent_file_path() -> Option<PathBuf> {
-    dirs::home_dir().map(|home| home.join(".mocha").join("recent.json"))
+    get_config_dir().map(|dir| dir.join("recent.json"))
+}
+
+/// Get the path to recent.json.bak in the config directory ($MOCHA_CONFIG_DIR or ~/.mocha)
+fn get_recent_backup_path() -> Option<PathBuf> {
+    get_config_dir().map(|dir| dir.join("recent.json.bak"))
+}
+
+/// Get the path to searches.json in the config directory ($MOCHA_CONFIG_DIR or ~/.mocha)
+fn get_saved_searches_path() -> Option<PathBuf> {
+    get_config_dir().map(|dir| dir.join("searches.json"))
+}
+
+/// Get the path to bookmarks.json in the config directory ($MOCHA_CONFIG_DIR or ~/.mocha)
+fn get_bookmarks_path() -> Option<PathBuf> {
+    get_config_dir().map(|dir| dir.join("bookmarks.json"))
+}
+
+/// Get the path to annotations.json in the config directory ($MOCHA_CONFIG_DIR or ~/.mocha)
+fn get_annotations_path() -> Option<PathBuf> {
+    get_config_dir().map(|dir| dir.join("annotations.json"))
+}
+
+/// Get the path to config.lock in the config directory ($MOCHA_CONFIG_DIR or ~/.mocha)
+fn get_config_lock_path() -> Option<PathBuf> {
+    get_config_dir().map(|dir| dir.join("config.lock"))
+}
+
+/// Get the path to sessions_log.json in the config directory ($MOCHA_CONFIG_DIR or ~/.mocha)
+fn get_sessions_log_path() -> Option<PathBuf> {
+    get_config_dir().map(|dir| dir.join("sessions_log.json"))
+}
+
+/// Get the path to sessions in the config directory ($MOCHA_CONFIG_DIR or ~/.mocha)
+fn get_sessions_dir() -> Option<PathBuf> {
+    get_config_dir().map(|dir| dir.join("sessions"))
+}
+
+/// Get the path to backups in the config directory ($MOCHA_CONFIG_DIR or ~/.mocha)
+fn get_backups_dir() -> Option<PathBuf> {
+    get_config_dir().map(|dir| dir.join("backups"))
+}
+
+/// Get the path to search_history.json in the config directory ($MOCHA_CONFIG_DIR or ~/.mocha)
+pub fn get_search_history_path() -> Option<PathBuf> {
+    get_config_dir().map(|dir| dir.join("search_history.json"))
+}
+
+/// Get the path to config.json in the config directory ($MOCHA_CONFIG_DIR or ~/.mocha)
+pub fn get_config_path() -> Option<PathBuf> {
+    get_config_dir().map(|dir| dir.join("config.json"))
+}
+
+/// Load a JSON list such as bookmarks.json, or an empty list if it is missing or unreadable
+pub fn load_json_list<T: serde::de::DeserializeOwned>(path: &Path) -> Vec<T> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+/// Write a JSON list back to disk, creating the config directory if needed
+/// The file is replaced atomically so a crash mid-write leaves the old list intact
+fn save_json_list<T: Serialize>(path: &Path, items: &[T]) -> bool {
+    if let Some(parent) = path.parent() {
+        if fs::create_dir_all(parent).is_err() {
+            return false;
+        }
+    }
+
+    match serde_json::to_string_pretty(items) {
+        Ok(json) => write_file_atomically(path, json.as_bytes()),
+        Err(_) => false,
+    }
+}
+
+/// Replace path with contents via a temp file in the same directory, so readers
+/// (and a crash mid-write) see either the old file or the new one, never a partial one
+fn write_file_atomically(path: &Path, contents: &[u8]) -> bool {
+    let mut temp = match temp_file_beside(path) {
+        Ok(t) => t,
+        Err(_) => return false,
+    };
+    temp.write_all(contents).is_ok() && temp.persist(path).is_ok()
+}
+
+/// Load the recent files list, or None if it is missing or unreadable
+fn load_recent_files(recent_path: &Path) -> Option<Vec<RecentFile>> {
+    fs::read_to_string(recent_path)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+}
+
+/// Write the recent files list back to disk, recording a new list version
+fn save_recent_files(state: &AppState, recent_path: &Path, files: &[RecentFile]) -> bool {
+    let stored: Vec<StoredRecentFile> = files.iter().map(StoredRecentFile::from).collect();
+    let json = match serde_json::to_string_pretty(&stored) {
+        Ok(j) => j,
+        Err(_) => return false,
+    };
+
+    // Seed the history with the pre-mutation list so the first delta is exact
+    if let Ok(mut history) = state.recent_history.lock() {
+        if history.snapshots.is_empty() {
+            let current = load_recent_files(recent_path).unwrap_or_default();
+            history.snapshots.push_back((0, current));
+        }
+    }
+
+    if !write_file_atomically(recent_path, json.as_bytes()) {
+        return false;
+    }
+
+    record_recent_snapshot(state, files);
+    true
+}
+
+/// Load the recent files list, let f change it, and save it if f reports a change,
+/// all under recent_files_lock so concurrent updates can't overwrite each other
+/// A missing or unreadable list starts out empty. f returns (result, changed);
+/// None if the config directory is unavailable or saving fails
+fn with_recent_files<T>(state: &AppState, f: impl FnOnce(&mut Vec<RecentFile>) -> (T, bool)) -> Option<T> {
+    let _guard = state.recent_files_lock.lock().ok()?;
+    let recent_path = get_recent_file_path()?;
+
+    let mut files = load_recent_files(&recent_path).unwrap_or_default();
+    let (result, changed) = f(&mut files);
+    if changed {
+        if let Some(parent) = recent_path.parent() {
+            fs::create_dir_all(parent).ok()?;
+        }
+        if !save_recent_files(state, &recent_path, &files) {
+            return None;
+        }
+    }
+    Some(result)
+}
+
+// Number of recent list snapshots kept for get_recent_files_since
+const MAX_RECENT_SNAPSHOTS: usize = 32;
+
+/// In-memory version history of the recent files list
+#[derive(Default)]
+pub struct RecentHistory {
+    version: u64,
+    snapshots: VecDeque<(u64, Vec<RecentFile>)>,
 }
 
-/// Extract filename from path
-fn get_filename(path: &str) -> String {
+/// Bump the list version and remember the list as written
+fn record_recent_snapshot(state: &AppState, files: &[RecentFile]) {
+    if let Ok(mut history) = state.recent_history.lock() {
+        history.version += 1;
+        let version = history.version;
+        history.snapshots.push_back((version, files.to_vec()));
+        while history.snapshots.len() > MAX_RECENT_SNAPSHOTS {
+            history.snapshots.pop_front();
+        }
+    }
+}
+
+/// Refresh every entry's metadata, keeping list order
+/// With the rayon feature the stats run in parallel, which helps most on network mounts
+fn refresh_recent_files(files: &mut [RecentFile]) {
+    refresh_recent_files_with(files, |path| fs::metadata(path).ok());
+}
+
+/// refresh_recent_files, reading each entry's metadata with stat
+fn refresh_recent_files_with(files: &mut [RecentFile], stat: impl Fn(&str) -> Option<fs::Metadata> + Sync) {
+    let refresh = |f: &mut RecentFile| apply_recent_metadata(f, stat(&f.path).as_ref());
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        files.par_iter_mut().for_each(refresh);
+    }
+    #[cfg(not(feature = "rayon"))]
+    files.iter_mut().for_each(refresh);
+}
+
+/// Set mtime, size, and exists from freshly read metadata (None if missing)
+fn apply_recent_metadata(f: &mut RecentFile, metadata: Option<&fs::Metadata>) {
+    f.metadata_fresh = true;
+    if let Some(metadata) = metadata {
+        f.exists = true;
+        f.size = Some(metadata.len());
+        f.mtime = mtime_millis(metadata);
+    } else {
+        f.exists = false;
+        f.size = None;
+        f.mtime = None;
+    }
+}
+
+/// File modification time in Unix millis
+fn mtime_millis(metadata: &fs::Metadata) -> Option<i64> {
+    metadata.modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as i64)
+}
+
+/// Unix millis as an RFC 3339 string in UTC, e.g. "2024-05-01T12:00:00.250+00:00"
+fn mtime_iso(mtime: i64) -> Option<String> {
+    DateTime::from_timestamp_millis(mtime).map(|t| t.to_rfc3339())
+}
+
+/// Extract filename from path, or the path itself if it has none (e.g. "/" or "..")
+pub fn get_filename(path: &str) -> String {
     std::path::Path::new(path)
         .file_name()
         .and_then(|n| n.to_str())
@@ -60,64 +476,259 @@ fn get_filename(path: &str) -> String {
         .to_string()
 }
 
-/// Read file with optional offset for differential/polling reads
-#[tauri::command]
-pub fn read_file(path: String, offset: u64) -> FileResult {
-    if path.is_empty() {
-        return FileResult {
-            success: false,
-            content: None,
-            path: None,
-            name: None,
-            size: None,
-            prev_size: None,
-            mtime: None,
-            truncated: None,
-            error: Some("No path provided".to_string()),
+/// Count U+FFFD replacement characters, e.g. those added by from_utf8_lossy
+fn count_replacements(s: &str) -> u32 {
+    s.matches('\u{FFFD}').count() as u32
+}
+
+/// Warning attached to successful reads whose content had invalid UTF-8 replaced
+fn encoding_warning(replacement_char_count: u32) -> Option<String> {
+    if replacement_char_count == 0 {
+        return None;
+    }
+    Some(format!(
+        "File contains {} non-UTF-8 byte sequences; consider re-encoding",
+        replacement_char_count
+    ))
+}
+
+/// Strip a trailing "\n" or "\r\n" from a line read with read_until
+fn trim_line_ending(line: &[u8]) -> &[u8] {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+/// Remove the part of each line matched by prefix_re at the start of the line
+/// Returns the rewritten content and how many lines had a prefix stripped
+fn strip_line_prefixes(content: &str, prefix_re: &Regex) -> (String, u32) {
+    let mut stripped = 0;
+    let mut out = String::with_capacity(content.len());
+
+    for line in content.split_inclusive('\n') {
+        match prefix_re.find(line) {
+            Some(m) if m.start() == 0 && m.end() > 0 => {
+                out.push_str(&line[m.end()..]);
+                stripped += 1;
+            }
+            _ => out.push_str(line),
+        }
+    }
+
+    (out, stripped)
+}
+
+/// Skip everything up to and including the first line ending
+/// A "\r\n" ending ends at its "\n" too, so no carriage return is left behind
+/// None if there is no line ending, i.e. s holds no complete line
+fn skip_partial_first_line(s: &str) -> Option<&str> {
+    let pos = s.find('\n')?;
+    Some(&s[pos + 1..])
+}
+
+// Reads larger than this are memory-mapped when the mmap-reads feature is enabled
+#[cfg(feature = "mmap-reads")]
+const MMAP_MIN_READ_SIZE: u64 = 1024 * 1024;
+
+/// Read read_size bytes starting at read_start and decode them as (lossy) UTF-8
+/// With mmap-reads, large reads are served from the page cache without a copy
+fn read_range_lossy(path: &str, read_start: u64, read_size: u64) -> Result<String, &'static str> {
+    let file = File::open(path).map_err(|_| "Cannot open file")?;
+    read_range_lossy_with_progress(file, read_start, read_size, |_| {})
+}
+
+// Chunk size for reads that report progress; one read-progress event per chunk
+const READ_PROGRESS_CHUNK_SIZE: usize = 512 * 1024;
+
+// Reads smaller than this finish quickly enough not to need read-progress events
+const READ_PROGRESS_MIN_SIZE: u64 = 1024 * 1024;
+
+/// read_range_lossy on an already opened file, calling on_progress with the bytes
+/// read so far after each chunk
+fn read_range_lossy_with_progress(
+    mut file: File,
+    read_start: u64,
+    read_size: u64,
+    mut on_progress: impl FnMut(u64),
+) -> Result<String, &'static str> {
+    // Files that can't be mapped (e.g. under /proc) fall back to a regular read.
+    // Touching mapped pages past the end of a file raises SIGBUS, so the length is
+    // re-checked on the open handle (a log may have been truncated since it was
+    // stat'ed) and only what exists now is mapped, then copied out straight away
+    #[cfg(feature = "mmap-reads")]
+    if read_size > MMAP_MIN_READ_SIZE {
+        let current_len = file.metadata().map(|m| m.len()).unwrap_or(0);
+        let map_size = read_size.min(current_len.saturating_sub(read_start));
+        let mapped = match map_size {
+            0 => Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof)),
+            _ => unsafe {
+                memmap2::MmapOptions::new()
+                    .offset(read_start)
+                    .len(map_size as usize)
+                    .map(&file)
+            },
         };
+        if let Ok(mmap) = mapped {
+            return Ok(String::from_utf8_lossy(&mmap).into_owned());
+        }
     }
 
-    // Get file metadata
-    let metadata = match fs::metadata(&path) {
-        Ok(m) => m,
-        Err(_) => {
-            return FileResult {
-                success: false,
-                content: None,
-                path: None,
-                name: None,
-                size: None,
-                prev_size: None,
-                mtime: None,
-                truncated: None,
-                error: Some("Cannot open file".to_string()),
-            };
+    // Seek to read position
+    if read_start > 0 {
+        if file.seek(SeekFrom::Start(read_start)).is_err() {
+            return Err("Cannot seek in file");
         }
-    };
+    }
 
-    let current_size = metadata.len();
-    let mtime = metadata.modified()
-        .ok()
-        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-        .map(|d| d.as_millis() as i64);
+    // Read content in chunks, stopping early if the file turns out to be shorter
+    let mut content = Vec::with_capacity(read_size as usize);
+    let mut chunk = vec![0u8; READ_PROGRESS_CHUNK_SIZE.min(read_size as usize)];
+    while (content.len() as u64) < read_size {
+        let wanted = chunk.len().min((read_size - content.len() as u64) as usize);
+        match file.read(&mut chunk[..wanted]) {
+            Ok(0) => break,
+            Ok(n) => {
+                content.extend_from_slice(&chunk[..n]);
+                on_progress(content.len() as u64);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(_) => break,
+        }
+    }
 
-    // If file size unchanged since last read, return empty (no new content)
-    if offset > 0 && current_size == offset {
-        return FileResult {
+    Ok(String::from_utf8_lossy(&content).into_owned())
+}
+
+/// Payload for the "read-progress" event emitted during large read_file reads
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadProgress {
+    pub path: String,
+    pub bytes_read: u64,
+    pub total_bytes: u64,
+    pub percent: u8,
+}
+
+/// What read_file reads for a request, worked out from the file's metadata
+struct ReadPlan {
+    /// The file size still matches offset, so there is nothing new to read
+    unchanged: bool,
+    path: String,
+    offset: u64,
+    prefix_re: Option<Regex>,
+    normalize_crlf: bool,
+    current_size: u64,
+    mtime: Option<i64>,
+    read_start: u64,
+    read_size: u64,
+    is_truncated: bool,
+    is_tail_read: bool,
+}
+
+impl ReadPlan {
+    /// Empty result for an unchanged file
+    fn unchanged_result(&self) -> FileResult {
+        FileResult {
             success: true,
             content: Some(String::new()),
-            path: Some(path.clone()),
-            name: Some(get_filename(&path)),
-            size: Some(current_size),
-            prev_size: Some(offset),
-            mtime,
+            path: Some(self.path.clone()),
+            name: Some(get_filename(&self.path)),
+            display_name: get_filename(&self.path),
+            size: Some(self.current_size),
+            read_from_offset: self.offset,
+            next_offset: self.current_size,
+            mtime: self.mtime,
+            mtime_iso: self.mtime.and_then(mtime_iso),
             truncated: Some(false),
+            prefix_pattern_used: self.prefix_re.as_ref().map(|re| re.as_str().to_string()),
+            lines_with_stripped_prefix: self.prefix_re.as_ref().map(|_| 0),
+            lines_returned: None,
+            start_line: None,
+            end_line: None,
+            total_lines: None,
+            anchor_line: None,
+            replacement_char_count: Some(0),
             error: None,
-        };
+            error_code: None,
+        }
+    }
+
+    /// Whether the read is large enough to emit "read-progress" events
+    fn reports_progress(&self) -> bool {
+        self.read_size >= READ_PROGRESS_MIN_SIZE
+    }
+
+    /// "read-progress" payload after bytes_read bytes
+    fn progress(&self, bytes_read: u64) -> ReadProgress {
+        ReadProgress {
+            path: self.path.clone(),
+            bytes_read,
+            total_bytes: self.read_size,
+            percent: (bytes_read * 100 / self.read_size) as u8,
+        }
+    }
+}
+
+/// Failed result for opening a file whose metadata was read just before
+/// The stat succeeded, so a file missing now was deleted in between
+fn open_after_stat_error(e: std::io::Error) -> FileResult {
+    match e.kind() {
+        std::io::ErrorKind::NotFound => FileResult::error_code(CommandError::FileDisappearedBetweenStatAndOpen),
+        _ => FileResult::error("Cannot open file"),
+    }
+}
+
+#[cfg(test)]
+type ReadHook = Box<dyn FnOnce(&str)>;
+
+#[cfg(test)]
+thread_local! {
+    /// Run once by the next read_file on this thread between its stat and open
+    pub(crate) static BETWEEN_STAT_AND_OPEN: std::cell::RefCell<Option<ReadHook>> = std::cell::RefCell::new(None);
+}
+
+/// Point between read_file's stat and open where tests can change the file
+fn after_read_stat(_path: &str) {
+    #[cfg(test)]
+    if let Some(hook) = BETWEEN_STAT_AND_OPEN.with(|h| h.borrow_mut().take()) {
+        hook(_path);
+    }
+}
+
+/// Check read_file's path and compile its strip_prefix_regex
+fn read_prefix_regex(path: &str, strip_prefix_regex: Option<&str>) -> Result<Option<Regex>, &'static str> {
+    if path.is_empty() {
+        return Err("No path provided");
+    }
+
+    match strip_prefix_regex.filter(|p| !p.is_empty()) {
+        Some(pattern) => Regex::new(pattern).map(Some).map_err(|_| "Invalid prefix regex"),
+        None => Ok(None),
     }
+}
+
+/// Decide where to read from and how much
+fn plan_read(
+    path: String,
+    offset: u64,
+    prefix_re: Option<Regex>,
+    normalize_crlf: bool,
+    expected_inode: Option<u64>,
+    metadata: &fs::Metadata,
+) -> ReadPlan {
+    let current_size = metadata.len();
+    let mtime = metadata.modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as i64);
+
+    // Rotation by rename + create can leave the new file as large as the old offset
+    let is_replaced = match (expected_inode, file_identity(metadata)) {
+        (Some(expected), Some((inode, _))) => inode != expected,
+        _ => false,
+    };
 
-    // If file shrunk since last read, it was truncated/replaced - read from start
-    let (read_start, is_truncated, is_tail_read) = if offset > 0 && current_size < offset {
+    // If file shrunk or was replaced since last read - read from start
+    let (read_start, is_truncated, is_tail_read) = if offset > 0 && (current_size < offset || is_replaced) {
         (0, true, false)  // Read entire file from beginning
     } else if offset > 0 {
         (offset, false, false)  // Normal differential read
@@ -131,355 +742,8822 @@ pub fn read_file(path: String, offset: u64) -> FileResult {
     let mut is_tail_read = is_tail_read;
 
     // For large files (initial read only), read just the tail
+    // Start one byte early so a tail that begins exactly on a line keeps that line:
+    // the extra byte is then the newline that skip_partial_first_line drops
     if read_size > MAX_READ_SIZE && offset == 0 {
-        actual_read_start = current_size - MAX_READ_SIZE;
-        read_size = MAX_READ_SIZE;
+        actual_read_start = current_size - MAX_READ_SIZE - 1;
+        read_size = MAX_READ_SIZE + 1;
         is_tail_read = true;
     }
 
-    // Open and read file
-    let mut file = match File::open(&path) {
+    ReadPlan {
+        unchanged: offset > 0 && current_size == offset && !is_replaced,
+        path,
+        offset,
+        prefix_re,
+        normalize_crlf,
+        current_size,
+        mtime,
+        read_start: actual_read_start,
+        read_size,
+        is_truncated,
+        is_tail_read,
+    }
+}
+
+/// Turn the bytes read for plan into read_file's result
+fn finish_read(state: &AppState, plan: ReadPlan, content_str: String) -> FileResult {
+    let ReadPlan { path, offset, prefix_re, normalize_crlf, current_size, mtime, .. } = plan;
+    let replacement_char_count = count_replacements(&content_str);
+
+    // Keep any cached line index in step: extend it over the appended bytes,
+    // or drop it once the file has been truncated or replaced
+    if let Ok(mut cache) = state.line_index_cache.lock() {
+        if plan.is_truncated {
+            cache.pop(&path);
+        } else if offset > 0 && current_size > offset {
+            if let Some(index) = cache.get_mut(&path).filter(|i| i.size == offset) {
+                if index.extend(Path::new(&path), mtime).is_err() {
+                    cache.pop(&path);
+                }
+            }
+        }
+    }
+
+    // For tail reads, skip partial first line (we may have started mid-line)
+    // A tail with no line ending at all is one huge partial line, so nothing is returned
+    let mut warning = None;
+    let content_str = if plan.is_tail_read {
+        match skip_partial_first_line(&content_str) {
+            Some(rest) => rest.to_string(),
+            None => {
+                warning = Some("No complete lines in tail window".to_string());
+                String::new()
+            }
+        }
+    } else {
+        content_str
+    };
+
+    let content_str = if normalize_crlf {
+        content_str.replace("\r\n", "\n")
+    } else {
+        content_str
+    };
+
+    // Optionally strip structured prefixes for cleaner display
+    let (content_str, lines_with_stripped_prefix) = match &prefix_re {
+        Some(re) => {
+            let (stripped, count) = strip_line_prefixes(&content_str, re);
+            (stripped, Some(count))
+        }
+        None => (content_str, None),
+    };
+
+    FileResult {
+        success: true,
+        content: Some(content_str),
+        path: Some(path.clone()),
+        name: Some(get_filename(&path)),
+        display_name: get_filename(&path),
+        size: Some(current_size),
+        read_from_offset: plan.read_start,
+        next_offset: current_size,
+        mtime,
+        mtime_iso: mtime.and_then(mtime_iso),
+        truncated: Some(plan.is_truncated || plan.is_tail_read),
+        prefix_pattern_used: prefix_re.as_ref().map(|re| re.as_str().to_string()),
+        lines_with_stripped_prefix,
+        lines_returned: None,
+        start_line: None,
+        end_line: None,
+        total_lines: None,
+        anchor_line: None,
+        replacement_char_count: Some(replacement_char_count),
+        error: warning.or_else(|| encoding_warning(replacement_char_count)),
+        error_code: None,
+    }
+}
+
+/// Read file with optional offset for differential/polling reads
+/// With strip_prefix_regex set, the matched prefix is removed from each line
+/// With normalize_crlf set, all "\r\n" line endings are returned as "\n"
+/// With expected_inode set, a file whose inode differs is treated as replaced and read from the start
+/// Reads of 1MB or more emit "read-progress" events every 512KB
+#[tauri::command]
+pub fn read_file(
+    window: Window,
+    state: State<'_, AppState>,
+    path: String,
+    offset: u64,
+    strip_prefix_regex: Option<String>,
+    normalize_crlf: bool,
+    expected_inode: Option<u64>,
+) -> FileResult {
+    read_file_with_progress(&state, path, offset, strip_prefix_regex, normalize_crlf, expected_inode, |progress| {
+        let _ = window.emit("read-progress", progress);
+    })
+}
+
+/// read_file, handing "read-progress" payloads to on_progress instead of a window
+fn read_file_with_progress(
+    state: &AppState,
+    path: String,
+    offset: u64,
+    strip_prefix_regex: Option<String>,
+    normalize_crlf: bool,
+    expected_inode: Option<u64>,
+    mut on_progress: impl FnMut(ReadProgress),
+) -> FileResult {
+    let prefix_re = match read_prefix_regex(&path, strip_prefix_regex.as_deref()) {
+        Ok(re) => re,
+        Err(e) => return FileResult::error(e),
+    };
+
+    // Get file metadata
+    let metadata = match fs::metadata(&path) {
+        Ok(m) => m,
+        Err(_) => return FileResult::error("Cannot open file"),
+    };
+    // If file size unchanged since last read, return empty (no new content)
+    let plan = plan_read(path, offset, prefix_re, normalize_crlf, expected_inode, &metadata);
+    if plan.unchanged {
+        return plan.unchanged_result();
+    }
+
+    // Open and read file
+    after_read_stat(&plan.path);
+    let file = match File::open(&plan.path) {
+        Ok(f) => f,
+        Err(e) => return open_after_stat_error(e),
+    };
+    let content_str = match read_range_lossy_with_progress(file, plan.read_start, plan.read_size, |bytes_read| {
+        if plan.reports_progress() {
+            on_progress(plan.progress(bytes_read));
+        }
+    }) {
+        Ok(s) => s,
+        Err(e) => return FileResult::error(e),
+    };
+
+    finish_read(state, plan, content_str)
+}
+
+/// read_file using tokio's async file I/O, so a slow disk or network mount doesn't
+/// tie up a thread or block IPC
+#[tauri::command]
+pub async fn read_file_async(
+    app: AppHandle,
+    window: Window,
+    path: String,
+    offset: u64,
+    strip_prefix_regex: Option<String>,
+    normalize_crlf: bool,
+    expected_inode: Option<u64>,
+) -> FileResult {
+    let state = app.state::<AppState>();
+    read_file_tokio(&state, path, offset, strip_prefix_regex, normalize_crlf, expected_inode, |progress| {
+        let _ = window.emit("read-progress", progress);
+    })
+    .await
+}
+
+/// read_file_async, handing "read-progress" payloads to on_progress instead of a window
+async fn read_file_tokio(
+    state: &AppState,
+    path: String,
+    offset: u64,
+    strip_prefix_regex: Option<String>,
+    normalize_crlf: bool,
+    expected_inode: Option<u64>,
+    mut on_progress: impl FnMut(ReadProgress),
+) -> FileResult {
+    use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+    let prefix_re = match read_prefix_regex(&path, strip_prefix_regex.as_deref()) {
+        Ok(re) => re,
+        Err(e) => return FileResult::error(e),
+    };
+
+    let metadata = match tokio::fs::metadata(&path).await {
+        Ok(m) => m,
+        Err(_) => return FileResult::error("Cannot open file"),
+    };
+    // If file size unchanged since last read, return empty (no new content)
+    let plan = plan_read(path, offset, prefix_re, normalize_crlf, expected_inode, &metadata);
+    if plan.unchanged {
+        return plan.unchanged_result();
+    }
+
+    after_read_stat(&plan.path);
+    let mut file = match tokio::fs::File::open(&plan.path).await {
+        Ok(f) => f,
+        Err(e) => return open_after_stat_error(e),
+    };
+    if plan.read_start > 0 && file.seek(SeekFrom::Start(plan.read_start)).await.is_err() {
+        return FileResult::error("Cannot seek in file");
+    }
+
+    // Read content in chunks, stopping early if the file turns out to be shorter
+    let mut content = Vec::with_capacity(plan.read_size as usize);
+    let mut chunk = vec![0u8; READ_PROGRESS_CHUNK_SIZE.min(plan.read_size as usize)];
+    while (content.len() as u64) < plan.read_size {
+        let wanted = chunk.len().min((plan.read_size - content.len() as u64) as usize);
+        match file.read(&mut chunk[..wanted]).await {
+            Ok(0) => break,
+            Ok(n) => {
+                content.extend_from_slice(&chunk[..n]);
+                if plan.reports_progress() {
+                    on_progress(plan.progress(content.len() as u64));
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(_) => break,
+        }
+    }
+
+    let content_str = String::from_utf8_lossy(&content).into_owned();
+    finish_read(state, plan, content_str)
+}
+
+/// Result for normalize_path command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NormalizedPathResult {
+    pub canonical: String,
+    pub original: String,
+    pub changed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Windows canonical paths use the verbatim "\\?\" form; strip it for plain
+/// drive paths so they match what the user and file dialogs see
+fn strip_verbatim_prefix(path: String) -> String {
+    if cfg!(windows) && path.starts_with(r"\\?\") && !path.starts_with(r"\\?\UNC\") {
+        path[4..].to_string()
+    } else {
+        path
+    }
+}
+
+/// Canonicalize a path (resolving symlinks and relative segments)
+/// On failure the original path is returned unchanged with an error
+#[tauri::command]
+pub fn normalize_path(path: String) -> NormalizedPathResult {
+    match fs::canonicalize(&path) {
+        Ok(canonical) => {
+            let canonical = strip_verbatim_prefix(canonical.to_string_lossy().into_owned());
+            NormalizedPathResult {
+                changed: canonical != path,
+                canonical,
+                original: path,
+                error: None,
+            }
+        }
+        Err(e) => NormalizedPathResult {
+            canonical: path.clone(),
+            original: path,
+            changed: false,
+            error: Some(format!("Cannot resolve path: {}", e)),
+        },
+    }
+}
+
+/// One page of the recent files list
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaginatedRecentFiles {
+    pub files: Vec<RecentFile>,
+    pub total: usize,
+    pub page: usize,
+    pub page_size: usize,
+    pub has_more: bool,
+}
+
+// How long get_recent_files waits on a single file's metadata (e.g. a stalled NFS mount)
+const METADATA_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Get list of recently opened files, optionally one page at a time
+/// Only the returned page's entries are refreshed from the filesystem; entries whose
+/// metadata doesn't arrive within METADATA_TIMEOUT keep their stored values
+#[tauri::command]
+pub async fn get_recent_files(app: AppHandle, page: Option<usize>, page_size: Option<usize>) -> PaginatedRecentFiles {
+    let page = page.unwrap_or(0);
+    let page_size = page_size.filter(|&n| n > 0).unwrap_or(MAX_RECENT);
+
+    let files = get_recent_file_path()
+        .and_then(|p| load_recent_files(&p))
+        .unwrap_or_default();
+
+    let total = files.len();
+    let start = page.saturating_mul(page_size).min(total);
+    let mut page_files: Vec<RecentFile> = files.into_iter().skip(start).take(page_size).collect();
+
+    let open_seconds = get_sessions_log_path()
+        .map(|p| session_totals(&app.state::<AppState>(), &p))
+        .unwrap_or_default();
+    for f in page_files.iter_mut() {
+        f.total_open_seconds = open_seconds.get(&f.path).copied();
+    }
+
+    // Stat every file on the page concurrently, each bounded by the timeout
+    let lookups: Vec<_> = page_files.iter()
+        .map(|f| {
+            let path = f.path.clone();
+            tauri::async_runtime::spawn(async move {
+                let metadata = tauri::async_runtime::spawn_blocking(move || fs::metadata(path).ok());
+                tokio::time::timeout(METADATA_TIMEOUT, metadata).await
+            })
+        })
+        .collect();
+
+    let mut files = Vec::with_capacity(page_files.len());
+    for (mut f, lookup) in page_files.into_iter().zip(lookups) {
+        match lookup.await {
+            Ok(Ok(Ok(metadata))) => apply_recent_metadata(&mut f, metadata.as_ref()),
+            // Timed out or the task failed: keep the stale values from recent.json
+            _ => f.metadata_fresh = false,
+        }
+        files.push(f);
+    }
+
+    PaginatedRecentFiles {
+        has_more: start + files.len() < total,
+        files,
+        total,
+        page,
+        page_size,
+    }
+}
+
+/// Add a file to the recent files list
+#[tauri::command]
+pub fn add_recent_file(state: State<'_, AppState>, path: String) -> bool {
+    if path.is_empty() {
+        return false;
+    }
+
+    // Store the canonical form so different spellings of a path dedupe;
+    // paths that can't be resolved (e.g. not created yet) are kept as given
+    let path = normalize_path(path).canonical;
+
+    with_recent_files(&state, |recent_files| {
+        // Keep the last good list as recent.json.bak before it is overwritten; copy rather
+        // than rename so recent.json is never missing if the write fails. An unreadable
+        // list loads as empty and must not replace a good backup
+        if !recent_files.is_empty() {
+            if let (Some(recent_path), Some(backup_path)) = (get_recent_file_path(), get_recent_backup_path()) {
+                let _ = fs::copy(recent_path, backup_path);
+            }
+        }
+
+        // Take out the existing entry (if any) so the user's settings on it survive re-adding
+        let previous = recent_files.iter()
+            .position(|f| f.path == path)
+            .map(|i| recent_files.remove(i));
+
+        // Get file metadata
+        let metadata = fs::metadata(&path).ok();
+        let mtime = metadata.as_ref().and_then(mtime_millis);
+        let size = metadata.as_ref().map(|m| m.len());
+
+        // Create new entry
+        let mut new_entry = RecentFile {
+            path: path.clone(),
+            name: get_filename(&path),
+            last_opened: Utc::now().timestamp_millis(),
+            mtime,
+            size,
+            exists: metadata.is_some(),
+            tags: vec![],
+            open_count: 1,
+            is_pinned: false,
+            alias: None,
+            metadata_fresh: metadata.is_some(),
+            group: None,
+            notes: None,
+            total_open_seconds: None,
+        };
+        if let Some(previous) = previous {
+            new_entry.tags = previous.tags;
+            new_entry.open_count = previous.open_count.saturating_add(1);
+            new_entry.is_pinned = previous.is_pinned;
+            new_entry.alias = previous.alias;
+            new_entry.group = previous.group;
+            new_entry.notes = previous.notes;
+        }
+
+        // Prepend new entry, keeping at most MAX_RECENT
+        recent_files.insert(0, new_entry);
+        recent_files.truncate(MAX_RECENT);
+        ((), true)
+    })
+    .is_some()
+}
+
+/// Remove a single file from the recent files list
+#[tauri::command]
+pub fn remove_recent_file(state: State<'_, AppState>, path: String) -> bool {
+    if path.is_empty() {
+        return false;
+    }
+
+    with_recent_files(&state, |files| {
+        let before = files.len();
+        files.retain(|f| f.path != path);
+        ((), files.len() != before)
+    })
+    .is_some()
+}
+
+/// Result for clear_recent_files command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClearResult {
+    pub success: bool,
+    pub removed: usize,
+    pub kept: usize,
+}
+
+/// Clear the recent files list
+/// With keep_pinned set, pinned entries survive the clear
+#[tauri::command]
+pub fn clear_recent_files(state: State<'_, AppState>, keep_pinned: bool) -> ClearResult {
+    let cleared = with_recent_files(&state, |files| {
+        let before = files.len();
+        if keep_pinned {
+            files.retain(|f| f.is_pinned);
+        } else {
+            files.clear();
+        }
+        let kept = files.len();
+        ((before - kept, kept), kept != before)
+    });
+
+    match cleared {
+        Some((removed, kept)) => ClearResult {
+            success: true,
+            removed,
+            kept,
+        },
+        None => ClearResult {
+            success: false,
+            removed: 0,
+            kept: 0,
+        },
+    }
+}
+
+/// Pin or unpin a recent file so it survives clear_recent_files(keep_pinned)
+#[tauri::command]
+pub fn set_recent_file_pinned(state: State<'_, AppState>, path: String, pinned: bool) -> bool {
+    with_recent_files(&state, |files| match files.iter_mut().find(|f| f.path == path) {
+        Some(entry) => {
+            entry.is_pinned = pinned;
+            (true, true)
+        }
+        None => (false, false),
+    })
+    .unwrap_or(false)
+}
+
+/// Result for export_file command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportResult {
+    pub success: bool,
+    pub created_dirs: bool,
+    pub bytes_written: u64,
+    pub bom_written: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Extra output options for export_file
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExportOptions {
+    /// Start the file with a UTF-8 byte order mark, so Excel and PowerShell detect UTF-8
+    pub include_bom: bool,
+}
+
+// UTF-8 byte order mark written by export_file with include_bom
+const UTF8_BOM: [u8; 3] = [0xEF, 0xBB, 0xBF];
+
+/// Validate that path could be written without touching its contents
+fn validate_export_path(path: &Path) -> Result<(), String> {
+    if path.is_dir() {
+        return Err("Path is a directory".to_string());
+    }
+
+    // Existing files are opened for writing without truncation
+    if path.exists() {
+        return OpenOptions::new()
+            .write(true)
+            .open(path)
+            .map(|_| ())
+            .map_err(|e| format!("Cannot write file: {}", e));
+    }
+
+    // New files: the nearest existing ancestor must accept new entries
+    let dir = path.ancestors()
+        .skip(1)
+        .find(|a| a.as_os_str().is_empty() || a.exists())
+        .map(|a| if a.as_os_str().is_empty() { Path::new(".") } else { a })
+        .unwrap_or(Path::new("."));
+
+    if check_write_permission_at_path(dir.to_string_lossy().into_owned()) {
+        Ok(())
+    } else {
+        Err("Cannot write file: permission denied".to_string())
+    }
+}
+
+/// Export content to a file (used for logbook export)
+/// Missing parent directories are created unless create_parents is Some(false)
+/// With dry_run set, the destination is validated but nothing is written
+#[tauri::command]
+pub fn export_file(
+    path: String,
+    content: String,
+    create_parents: Option<bool>,
+    dry_run: bool,
+    options: Option<ExportOptions>,
+) -> ExportResult {
+    let failed = |error: String| ExportResult {
+        success: false,
+        created_dirs: false,
+        bytes_written: 0,
+        bom_written: false,
+        error: Some(error),
+    };
+    let options = options.unwrap_or_default();
+
+    if path.is_empty() {
+        return failed("No path provided".to_string());
+    }
+
+    let parent_missing = Path::new(&path)
+        .parent()
+        .is_some_and(|p| !p.as_os_str().is_empty() && !p.exists());
+
+    if parent_missing && !create_parents.unwrap_or(true) {
+        return failed("Parent directory does not exist".to_string());
+    }
+
+    if dry_run {
+        return match validate_export_path(Path::new(&path)) {
+            Ok(_) => ExportResult {
+                success: true,
+                created_dirs: false,
+                bytes_written: 0,
+                bom_written: false,
+                error: None,
+            },
+            Err(e) => failed(e),
+        };
+    }
+
+    let mut created_dirs = false;
+    if parent_missing {
+        if let Some(parent) = Path::new(&path).parent() {
+            if let Err(e) = fs::create_dir_all(parent) {
+                return failed(format!("Cannot create parent directory: {}", e));
+            }
+            created_dirs = true;
+        }
+    }
+
+    // Content that already starts with a BOM doesn't get a second one
+    let bom_written = options.include_bom && !content.starts_with('\u{FEFF}');
+    let mut bytes = Vec::with_capacity(content.len() + UTF8_BOM.len());
+    if bom_written {
+        bytes.extend_from_slice(&UTF8_BOM);
+    }
+    bytes.extend_from_slice(content.as_bytes());
+
+    match fs::write(&path, &bytes) {
+        Ok(_) => ExportResult {
+            success: true,
+            created_dirs,
+            bytes_written: bytes.len() as u64,
+            bom_written,
+            error: None,
+        },
+        Err(e) => ExportResult {
+            success: false,
+            created_dirs,
+            bytes_written: 0,
+            bom_written: false,
+            error: Some(format!("Cannot write file: {}", e)),
+        },
+    }
+}
+
+/// Check that files can be created in a directory by creating and removing a temp dir
+/// More reliable than inspecting permission bits, especially on Windows
+#[tauri::command]
+pub fn check_write_permission_at_path(dir_path: String) -> bool {
+    if dir_path.is_empty() {
+        return false;
+    }
+
+    match tempfile::Builder::new().prefix(".mocha-write-check").tempdir_in(&dir_path) {
+        Ok(dir) => dir.close().is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Result for search_file_for_line command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchLineResult {
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_number: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_lines: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_time_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes_scanned: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub throughput_mb_per_sec: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub byte_offset: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_start_offset: Option<u64>,
+    /// True if only a line range was searched rather than the whole file
+    pub range_bounded: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Search for a specific line in a file and return surrounding context
+/// Used for "jump to source" when the log is outside the truncated view
+/// With benchmark set, timing and throughput for the scan are also returned
+/// Byte offsets of the match and first context line can be passed to read_file
+/// With line_range set, only lines start..=end (1-indexed) can match, so the same
+/// message elsewhere in the file isn't picked up; context may extend past the range.
+/// The scan then stops after the range, leaving total_lines unset unless it reached the end
+#[tauri::command]
+pub fn search_file_for_line(
+    state: State<'_, AppState>,
+    path: String,
+    search_line: String,
+    context_lines: usize,
+    benchmark: bool,
+    line_range: Option<(usize, usize)>,
+) -> SearchLineResult {
+    let range_bounded = line_range.is_some();
+    let failed = |error: &str| SearchLineResult {
+        success: false,
+        content: None,
+        line_number: None,
+        total_lines: None,
+        search_time_ms: None,
+        bytes_scanned: None,
+        throughput_mb_per_sec: None,
+        byte_offset: None,
+        context_start_offset: None,
+        range_bounded,
+        error: Some(error.to_string()),
+    };
+
+    if path.is_empty() || search_line.is_empty() {
+        return failed("Invalid parameters");
+    }
+    let (range_start, range_end) = line_range.unwrap_or((1, usize::MAX));
+    if range_start == 0 || range_end < range_start {
+        return failed("Invalid line range");
+    }
+
+    let started = Instant::now();
+
+    let file = match File::open(&path) {
+        Ok(f) => f,
+        Err(_) => return failed("Cannot read file"),
+    };
+
+    // Stream the file line by line, keeping only the context window in memory
+    let mut reader = BufReader::new(file);
+    // Pre-context lines paired with their starting byte offsets
+    let mut before: VecDeque<(u64, String)> = VecDeque::with_capacity(context_lines);
+    let mut context: Vec<String> = Vec::new();
+    let mut found_index: Option<usize> = None;
+    let mut byte_offset: Option<u64> = None;
+    let mut context_start_offset: Option<u64> = None;
+    let mut remaining_after = 0;
+    let mut total_lines = 0;
+    // False if the scan stopped past line_range, so total_lines isn't the file's total
+    let mut scanned_whole_file = true;
+    let mut bytes_scanned: u64 = 0;
+    let mut buf = Vec::new();
+
+    loop {
+        buf.clear();
+        let line_offset = bytes_scanned;
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) => break,
+            Ok(n) => bytes_scanned += n as u64,
+            Err(_) => return failed("Cannot read file"),
+        }
+
+        let idx = total_lines;
+        total_lines += 1;
+
+        // Nothing past the range can match, so stop once any after-context is in
+        if total_lines > range_end && remaining_after == 0 {
+            scanned_whole_file = false;
+            break;
+        }
+
+        match found_index {
+            None => {
+                let line = String::from_utf8_lossy(trim_line_ending(&buf));
+                if (range_start..=range_end).contains(&total_lines) && line == search_line {
+                    found_index = Some(idx);
+                    byte_offset = Some(line_offset);
+                    context_start_offset = Some(before.front().map_or(line_offset, |(o, _)| *o));
+                    context.extend(before.drain(..).map(|(_, l)| l));
+                    context.push(line.into_owned());
+                    remaining_after = context_lines;
+                } else if context_lines > 0 {
+                    if before.len() == context_lines {
+                        before.pop_front();
+                    }
+                    before.push_back((line_offset, line.into_owned()));
+                }
+            }
+            Some(_) if remaining_after > 0 => {
+                context.push(String::from_utf8_lossy(trim_line_ending(&buf)).into_owned());
+                remaining_after -= 1;
+            }
+            // Keep scanning so total_lines is still accurate
+            Some(_) => {}
+        }
+    }
+
+    // Benchmark timing covers only the scan, not IPC serialization
+    let (search_time_ms, bytes_scanned, throughput_mb_per_sec) = if benchmark {
+        let elapsed = started.elapsed();
+        let secs = elapsed.as_secs_f64();
+        let throughput = if secs > 0.0 {
+            bytes_scanned as f64 / (1024.0 * 1024.0) / secs
+        } else {
+            0.0
+        };
+        (Some(elapsed.as_millis() as u64), Some(bytes_scanned), Some(throughput))
+    } else {
+        (None, None, None)
+    };
+
+    record_search_history(&state, SearchHistoryEntry {
+        query: search_line,
+        file_path: path,
+        use_regex: false,
+        case_sensitive: true,
+        timestamp: Utc::now().timestamp_millis(),
+        result_count: if found_index.is_some() { 1 } else { 0 },
+    });
+
+    match found_index {
+        Some(idx) => {
+            SearchLineResult {
+                success: true,
+                content: Some(context.join("\n")),
+                line_number: Some(idx + 1), // 1-indexed
+                total_lines: scanned_whole_file.then_some(total_lines),
+                search_time_ms,
+                bytes_scanned,
+                throughput_mb_per_sec,
+                byte_offset,
+                context_start_offset,
+                range_bounded,
+                error: None,
+            }
+        }
+        None => {
+            SearchLineResult {
+                success: false,
+                content: None,
+                line_number: None,
+                total_lines: scanned_whole_file.then_some(total_lines),
+                search_time_ms,
+                bytes_scanned,
+                throughput_mb_per_sec,
+                byte_offset: None,
+                context_start_offset: None,
+                range_bounded,
+                error: Some(if range_bounded { "Line not found in range" } else { "Line not found in file" }.to_string()),
+            }
+        }
+    }
+}
+
+/// search_file_for_line on a background thread, so a slow disk doesn't block IPC
+#[tauri::command]
+pub async fn search_file_async(
+    app: AppHandle,
+    path: String,
+    search_line: String,
+    context_lines: usize,
+    benchmark: bool,
+    line_range: Option<(usize, usize)>,
+) -> SearchLineResult {
+    let range_bounded = line_range.is_some();
+    let search = tauri::async_runtime::spawn_blocking(move || {
+        search_file_for_line(app.state::<AppState>(), path, search_line, context_lines, benchmark, line_range)
+    });
+
+    match search.await {
+        Ok(result) => result,
+        Err(_) => SearchLineResult {
+            success: false,
+            content: None,
+            line_number: None,
+            total_lines: None,
+            search_time_ms: None,
+            bytes_scanned: None,
+            throughput_mb_per_sec: None,
+            byte_offset: None,
+            context_start_offset: None,
+            range_bounded,
+            error: Some("Search task failed".to_string()),
+        },
+    }
+}
+
+/// OS-level event types that watch_file can be filtered to
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum WatchEventType {
+    Modify,
+    Create,
+    Remove,
+    Rename,
+    All,
+}
+
+/// Payload for the "file-changed" event
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileChangedEvent {
+    pub path: String,
+    pub kind: WatchEventType,
+}
+
+/// Payload for the "file-rotated" event
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileRotatedEvent {
+    pub path: String,
+    pub old_size: u64,
+    pub new_size: u64,
+    pub mtime: i64,
+    pub mtime_iso: String,
+}
+
+/// Diagnostics collected by an active watcher
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchStats {
+    pub started_at: i64,
+    pub total_events: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_event_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_event_at: Option<i64>,
+    pub bytes_added: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub average_event_interval_ms: Option<f64>,
+}
+
+impl WatchStats {
+    fn new() -> Self {
+        WatchStats {
+            started_at: Utc::now().timestamp_millis(),
+            total_events: 0,
+            first_event_at: None,
+            last_event_at: None,
+            bytes_added: 0,
+            average_event_interval_ms: None,
+        }
+    }
+
+    fn record_event(&mut self, bytes_added: u64) {
+        let now = Utc::now().timestamp_millis();
+        self.total_events += 1;
+        self.bytes_added += bytes_added;
+        let first = *self.first_event_at.get_or_insert(now);
+        self.last_event_at = Some(now);
+        if self.total_events > 1 {
+            self.average_event_interval_ms =
+                Some((now - first) as f64 / (self.total_events - 1) as f64);
+        }
+    }
+}
+
+/// A registered watcher and the stats its event handler updates
+pub struct ActiveWatcher {
+    _watcher: RecommendedWatcher,
+    stats: Arc<Mutex<WatchStats>>,
+}
+
+/// Map a notify event kind to the event types exposed to the frontend
+fn classify_event(kind: &EventKind) -> Option<WatchEventType> {
+    match kind {
+        EventKind::Modify(ModifyKind::Name(_)) => Some(WatchEventType::Rename),
+        EventKind::Modify(_) => Some(WatchEventType::Modify),
+        EventKind::Create(_) => Some(WatchEventType::Create),
+        EventKind::Remove(_) => Some(WatchEventType::Remove),
+        _ => None,
+    }
+}
+
+/// Absolute, symlink-free form of a path to watch, matching the paths notify reports
+/// (e.g. /private/var/... for /var/... on macOS); a file that doesn't exist yet is
+/// resolved through its parent directory
+fn resolve_watch_target(path: &str) -> Option<PathBuf> {
+    if let Ok(target) = fs::canonicalize(path) {
+        return Some(target);
+    }
+    let path = Path::new(path);
+    let name = path.file_name()?;
+    let dir = match path.parent() {
+        Some(d) if !d.as_os_str().is_empty() => d,
+        _ => Path::new("."),
+    };
+    fs::canonicalize(dir).ok().map(|d| d.join(name))
+}
+
+/// Watch a file and emit "file-changed" events to the frontend
+/// An empty event_filter (or one containing All) emits for every event type
+#[tauri::command]
+pub fn watch_file(app: AppHandle, state: State<'_, AppState>, path: String, event_filter: Vec<WatchEventType>) -> bool {
+    if path.is_empty() {
+        return false;
+    }
+
+    let target = match resolve_watch_target(&path) {
+        Some(t) => t,
+        None => return false,
+    };
+    // Watch the parent directory so Create/Rename events survive log rotation
+    let dir = match target.parent() {
+        Some(d) => d.to_path_buf(),
+        None => return false,
+    };
+
+    let emit_all = event_filter.is_empty() || event_filter.contains(&WatchEventType::All);
+    let event_path = path.clone();
+    let mut last_size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    let stats = Arc::new(Mutex::new(WatchStats::new()));
+    let event_stats = stats.clone();
+
+    let watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        let event = match res {
+            Ok(e) => e,
+            Err(_) => return,
+        };
+
+        // Ignore events for other files in the same directory
+        if !event.paths.iter().any(|p| p == &target) {
+            return;
+        }
+
+        // Access and other events the frontend never sees don't count towards the stats
+        let kind = match classify_event(&event.kind) {
+            Some(k) => k,
+            None => return,
+        };
+
+        // A shrinking file was truncated or replaced - tell the frontend right away
+        let mut bytes_added = 0;
+        if let Ok(metadata) = fs::metadata(&target) {
+            let new_size = metadata.len();
+            if new_size < last_size {
+                let mtime = mtime_millis(&metadata).unwrap_or(0);
+                let _ = app.emit("file-rotated", FileRotatedEvent {
+                    path: event_path.clone(),
+                    old_size: last_size,
+                    new_size,
+                    mtime,
+                    mtime_iso: mtime_iso(mtime).unwrap_or_default(),
+                });
+            } else {
+                bytes_added = new_size - last_size;
+            }
+            last_size = new_size;
+        }
+
+        if let Ok(mut stats) = event_stats.lock() {
+            stats.record_event(bytes_added);
+        }
+
+        if !emit_all && !event_filter.contains(&kind) {
+            return;
+        }
+
+        let _ = app.emit("file-changed", FileChangedEvent {
+            path: event_path.clone(),
+            kind,
+        });
+    });
+
+    let mut watcher = match watcher {
+        Ok(w) => w,
+        Err(_) => return false,
+    };
+
+    if watcher.watch(&dir, RecursiveMode::NonRecursive).is_err() {
+        return false;
+    }
+
+    // Replacing an existing watcher drops it, which stops the old watch
+    match state.watchers.lock() {
+        Ok(mut w) => {
+            w.insert(path, ActiveWatcher {
+                _watcher: watcher,
+                stats,
+            });
+        }
+        Err(_) => return false,
+    }
+
+    // Past the limit new watches fail and changes go unnoticed, so warn ahead of it
+    let inotify = inotify_stats(&state);
+    if let Some(max) = inotify.max_user_watches {
+        if inotify.current_watches as u64 * 10 >= max * 9 {
+            log::warn!(
+                "{} of {} inotify watches in use; raise fs.inotify.max_user_watches to keep watching files",
+                inotify.current_watches,
+                max
+            );
+        }
+    }
+    true
+}
+
+/// Stop watching a file
+#[tauri::command]
+pub fn unwatch_file(state: State<'_, AppState>, path: String) -> bool {
+    match state.watchers.lock() {
+        Ok(mut w) => w.remove(&path).is_some(),
+        Err(_) => false,
+    }
+}
+
+// Quiet period after a change before a tail-update is sent, so bursts of writes become one event
+const TAIL_FOLLOW_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(50);
+
+/// Payload for the "tail-update" event
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TailUpdateEvent {
+    pub path: String,
+    /// Content appended since the previous update, or the whole file after a truncation
+    pub content: String,
+    /// True when more than MAX_READ_SIZE was new, so only the last complete lines are sent
+    pub truncated: bool,
+    pub new_size: u64,
+    pub mtime: i64,
+    /// mtime as an RFC 3339 string in UTC
+    pub mtime_iso: String,
+}
+
+/// A file followed with start_tail_follow; dropping it stops the watch and the task
+pub struct TailFollower {
+    _watcher: RecommendedWatcher,
+    task: tauri::async_runtime::JoinHandle<()>,
+}
+
+impl Drop for TailFollower {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Emit whatever was written since offset and return the new offset
+/// Nothing is emitted when there is no new content unless always is set
+fn emit_tail_update(app: &AppHandle, path: &str, offset: u64, always: bool) -> u64 {
+    let metadata = match fs::metadata(path) {
+        Ok(m) => m,
+        Err(_) => return offset,
+    };
+    let new_size = metadata.len();
+    // A shrinking file was truncated or replaced, so start over from the beginning
+    let offset = if new_size < offset { 0 } else { offset };
+    if new_size == offset && !always {
+        return offset;
+    }
+
+    // Past MAX_READ_SIZE send only the tail, starting one byte early so that a tail
+    // beginning exactly on a line keeps it once skip_partial_first_line drops the newline
+    let truncated = new_size - offset > MAX_READ_SIZE;
+    let read_start = if truncated { new_size - MAX_READ_SIZE - 1 } else { offset };
+    let content = match read_range_lossy(path, read_start, new_size - read_start) {
+        Ok(c) => c,
+        Err(_) => return offset,
+    };
+    let content = if truncated {
+        skip_partial_first_line(&content).unwrap_or_default().to_string()
+    } else {
+        content
+    };
+
+    let mtime = mtime_millis(&metadata).unwrap_or(0);
+    let _ = app.emit("tail-update", TailUpdateEvent {
+        path: path.to_string(),
+        content,
+        truncated,
+        new_size,
+        mtime,
+        mtime_iso: mtime_iso(mtime).unwrap_or_default(),
+    });
+    new_size
+}
+
+/// Push "tail-update" events for a file as it changes instead of polling read_file
+/// The first event carries the last initial_lines lines; later ones only new content
+#[tauri::command]
+pub fn start_tail_follow(app: AppHandle, state: State<'_, AppState>, path: String, initial_lines: usize) -> bool {
+    if path.is_empty() {
+        return false;
+    }
+
+    let tail = get_file_tail_offset(path.clone(), initial_lines);
+    if !tail.success {
+        return false;
+    }
+
+    // notify reports absolute paths with symlinks resolved, so compare against the same
+    let target = match fs::canonicalize(&path) {
+        Ok(p) => p,
+        Err(_) => return false,
+    };
+    // Watch the parent directory so the follow survives log rotation
+    let dir = match target.parent() {
+        Some(d) => d.to_path_buf(),
+        None => return false,
+    };
+
+    let (changes, mut changed) = tokio::sync::mpsc::unbounded_channel();
+    let watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        if let Ok(event) = res {
+            if event.paths.iter().any(|p| p == &target) {
+                let _ = changes.send(());
+            }
+        }
+    });
+    let mut watcher = match watcher {
+        Ok(w) => w,
+        Err(_) => return false,
+    };
+    if watcher.watch(&dir, RecursiveMode::NonRecursive).is_err() {
+        return false;
+    }
+
+    let follow_path = path.clone();
+    let task = tauri::async_runtime::spawn(async move {
+        let mut offset = tail.byte_offset;
+        let mut always = true;
+        loop {
+            let (app, path) = (app.clone(), follow_path.clone());
+            offset = tauri::async_runtime::spawn_blocking(move || emit_tail_update(&app, &path, offset, always))
+                .await
+                .unwrap_or(offset);
+            always = false;
+
+            if changed.recv().await.is_none() {
+                break;
+            }
+            tokio::time::sleep(TAIL_FOLLOW_DEBOUNCE).await;
+            while changed.try_recv().is_ok() {}
+        }
+    });
+
+    // Replacing an existing follower drops it, which stops the old one
+    match state.tail_followers.lock() {
+        Ok(mut f) => {
+            f.insert(path, TailFollower {
+                _watcher: watcher,
+                task,
+            });
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// Stop a follow started with start_tail_follow
+#[tauri::command]
+pub fn stop_tail_follow(state: State<'_, AppState>, path: String) -> bool {
+    match state.tail_followers.lock() {
+        Ok(mut f) => f.remove(&path).is_some(),
+        Err(_) => false,
+    }
+}
+
+/// Get the diagnostics collected by the watcher for a path
+#[tauri::command]
+pub fn get_watch_stats(state: State<'_, AppState>, path: String) -> Option<WatchStats> {
+    let watchers = state.watchers.lock().ok()?;
+    let active = watchers.get(&path)?;
+    let stats = active.stats.lock().ok()?;
+    Some(stats.clone())
+}
+
+/// Reset the diagnostics for a watched path
+#[tauri::command]
+pub fn reset_watch_stats(state: State<'_, AppState>, path: String) -> bool {
+    let watchers = match state.watchers.lock() {
+        Ok(w) => w,
+        Err(_) => return false,
+    };
+
+    let active = match watchers.get(&path) {
+        Some(a) => a,
+        None => return false,
+    };
+
+    let reset = match active.stats.lock() {
+        Ok(mut stats) => {
+            *stats = WatchStats::new();
+            true
+        }
+        Err(_) => false,
+    };
+    reset
+}
+
+/// An active watch and its diagnostics
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveWatch {
+    pub path: String,
+    pub stats: WatchStats,
+}
+
+/// List all active file watches with their stats
+#[tauri::command]
+pub fn list_active_watches(state: State<'_, AppState>) -> Vec<ActiveWatch> {
+    let watchers = match state.watchers.lock() {
+        Ok(w) => w,
+        Err(_) => return vec![],
+    };
+
+    watchers.iter()
+        .filter_map(|(path, active)| {
+            let stats = active.stats.lock().ok()?.clone();
+            Some(ActiveWatch {
+                path: path.clone(),
+                stats,
+            })
+        })
+        .collect()
+}
+
+/// Kinds of directory entry change reported by watch_directory
+#[derive(Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DirectoryChangeKind {
+    Created,
+    Deleted,
+    Modified,
+}
+
+/// Payload for the "directory-changed" event
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryChangedEvent {
+    pub path: String,
+    pub kind: DirectoryChangeKind,
+    pub entry_name: String,
+}
+
+/// Map a notify event to a directory change; renames count as a create or delete
+fn classify_directory_event(kind: &EventKind, entry: &Path) -> Option<DirectoryChangeKind> {
+    match kind {
+        EventKind::Create(_) => Some(DirectoryChangeKind::Created),
+        EventKind::Remove(_) => Some(DirectoryChangeKind::Deleted),
+        EventKind::Modify(ModifyKind::Name(_)) if entry.exists() => Some(DirectoryChangeKind::Created),
+        EventKind::Modify(ModifyKind::Name(_)) => Some(DirectoryChangeKind::Deleted),
+        EventKind::Modify(_) => Some(DirectoryChangeKind::Modified),
+        _ => None,
+    }
+}
+
+/// True if entry has one of the extensions (given with or without a leading dot)
+fn matches_extension(entry: &Path, extensions: &[String]) -> bool {
+    let ext = match entry.extension().and_then(|e| e.to_str()) {
+        Some(e) => e,
+        None => return false,
+    };
+    extensions.iter().any(|allowed| allowed.trim_start_matches('.').eq_ignore_ascii_case(ext))
+}
+
+/// Watch a directory and emit "directory-changed" events as entries come and go
+/// With extension_filter set, only matching entries are reported
+/// With auto_add set, newly created matching files are added to the recent list
+#[tauri::command]
+pub fn watch_directory(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    extension_filter: Option<Vec<String>>,
+    auto_add: bool,
+) -> bool {
+    if path.is_empty() || !Path::new(&path).is_dir() {
+        return false;
+    }
+
+    let dir_path = path.clone();
+    let watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        let event = match res {
+            Ok(e) => e,
+            Err(_) => return,
+        };
+
+        for entry in &event.paths {
+            if let Some(extensions) = &extension_filter {
+                if !matches_extension(entry, extensions) {
+                    continue;
+                }
+            }
+
+            let kind = match classify_directory_event(&event.kind, entry) {
+                Some(k) => k,
+                None => continue,
+            };
+
+            if auto_add && kind == DirectoryChangeKind::Created && entry.is_file() {
+                add_recent_file(app.state::<AppState>(), entry.to_string_lossy().to_string());
+            }
+
+            let _ = app.emit("directory-changed", DirectoryChangedEvent {
+                path: dir_path.clone(),
+                kind,
+                entry_name: get_filename(&entry.to_string_lossy()),
+            });
+        }
+    });
+
+    let mut watcher = match watcher {
+        Ok(w) => w,
+        Err(_) => return false,
+    };
+
+    if watcher.watch(Path::new(&path), RecursiveMode::NonRecursive).is_err() {
+        return false;
+    }
+
+    // Replacing an existing watcher drops it, which stops the old watch
+    match state.directory_watchers.lock() {
+        Ok(mut w) => {
+            w.insert(path, watcher);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// Stop watching a directory
+#[tauri::command]
+pub fn unwatch_directory(state: State<'_, AppState>, path: String) -> bool {
+    match state.directory_watchers.lock() {
+        Ok(mut w) => w.remove(&path).is_some(),
+        Err(_) => false,
+    }
+}
+
+/// List directories currently being watched
+#[tauri::command]
+pub fn get_watched_directories(state: State<'_, AppState>) -> Vec<String> {
+    match state.directory_watchers.lock() {
+        Ok(w) => {
+            let mut paths: Vec<String> = w.keys().cloned().collect();
+            paths.sort();
+            paths
+        }
+        Err(_) => vec![],
+    }
+}
+
+/// Result for get_file_change_velocity command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VelocityResult {
+    pub success: bool,
+    pub bytes_per_second: f64,
+    pub estimated_lines_per_second: f64,
+    pub measurement_window_seconds: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+// Cap on how much of the appended data is sampled to estimate line length
+const VELOCITY_SAMPLE_SIZE: u64 = 1024 * 1024;
+
+/// Measure how fast a file is growing over a window of window_seconds
+/// The two size readings are taken on a background thread so IPC isn't blocked
+#[tauri::command]
+pub async fn get_file_change_velocity(path: String, window_seconds: u64) -> VelocityResult {
+    let failed = |error: &str| VelocityResult {
+        success: false,
+        bytes_per_second: 0.0,
+        estimated_lines_per_second: 0.0,
+        measurement_window_seconds: window_seconds,
+        error: Some(error.to_string()),
+    };
+
+    if path.is_empty() || window_seconds == 0 {
+        return failed("Invalid parameters");
+    }
+
+    let start_size = match fs::metadata(&path) {
+        Ok(m) => m.len(),
+        Err(_) => return failed("Cannot open file"),
+    };
+
+    let sleep_path = path.clone();
+    let end_size = tauri::async_runtime::spawn_blocking(move || {
+        std::thread::sleep(std::time::Duration::from_secs(window_seconds));
+        fs::metadata(&sleep_path).map(|m| m.len()).ok()
+    })
+    .await;
+
+    let end_size = match end_size {
+        Ok(Some(s)) => s,
+        _ => return failed("Cannot open file"),
+    };
+
+    if end_size == start_size {
+        return failed("File did not change during measurement window");
+    }
+    if end_size < start_size {
+        return failed("File was truncated during measurement window");
+    }
+
+    let bytes_added = end_size - start_size;
+    let bytes_per_second = bytes_added as f64 / window_seconds as f64;
+
+    // Estimate line rate from the newline density of the appended bytes
+    let sample_size = bytes_added.min(VELOCITY_SAMPLE_SIZE);
+    let mut sample = Vec::with_capacity(sample_size as usize);
+    if let Ok(mut file) = File::open(&path) {
+        if file.seek(SeekFrom::Start(start_size)).is_ok() {
+            file.take(sample_size).read_to_end(&mut sample).ok();
+        }
+    }
+    let newlines = sample.iter().filter(|&&b| b == b'\n').count();
+    let estimated_lines_per_second = if sample.is_empty() {
+        0.0
+    } else {
+        bytes_per_second * newlines as f64 / sample.len() as f64
+    };
+
+    VelocityResult {
+        success: true,
+        bytes_per_second,
+        estimated_lines_per_second,
+        measurement_window_seconds: window_seconds,
+        error: None,
+    }
+}
+
+/// Tags must be non-empty and comma-free (reserved for list serialization)
+fn is_valid_tag(tag: &str) -> bool {
+    !tag.trim().is_empty() && !tag.contains(',')
+}
+
+/// Add tags to a recent file entry, keeping any tags it already has
+#[tauri::command]
+pub fn tag_recent_file(state: State<'_, AppState>, path: String, tags: Vec<String>) -> bool {
+    if path.is_empty() || tags.is_empty() || !tags.iter().all(|t| is_valid_tag(t)) {
+        return false;
+    }
+
+    with_recent_files(&state, |files| {
+        let entry = match files.iter_mut().find(|f| f.path == path) {
+            Some(e) => e,
+            None => return (false, false),
+        };
+
+        for tag in tags {
+            let tag = tag.trim().to_string();
+            if !entry.tags.contains(&tag) {
+                entry.tags.push(tag);
+            }
+        }
+        (true, true)
+    })
+    .unwrap_or(false)
+}
+
+/// Remove a single tag from a recent file entry
+#[tauri::command]
+pub fn remove_tag_from_recent_file(state: State<'_, AppState>, path: String, tag: String) -> bool {
+    if path.is_empty() || !is_valid_tag(&tag) {
+        return false;
+    }
+
+    with_recent_files(&state, |files| {
+        let entry = match files.iter_mut().find(|f| f.path == path) {
+            Some(e) => e,
+            None => return (false, false),
+        };
+
+        let tag = tag.trim();
+        entry.tags.retain(|t| t != tag);
+        (true, true)
+    })
+    .unwrap_or(false)
+}
+
+/// Get the recent files that carry the given tag
+#[tauri::command]
+pub fn get_recent_files_by_tag(tag: String) -> Vec<RecentFile> {
+    let recent_path = match get_recent_file_path() {
+        Some(p) => p,
+        None => return vec![],
+    };
+
+    let recent_files = load_recent_files(&recent_path).unwrap_or_default();
+    let tag = tag.trim();
+
+    let mut tagged: Vec<RecentFile> = recent_files.into_iter()
+        .filter(|f| f.tags.iter().any(|t| t == tag))
+        .collect();
+    refresh_recent_files(&mut tagged);
+    tagged
+}
+
+/// Filter the recent files list by a substring of name, alias, path or any tag
+/// An empty query returns every entry. Metadata is refreshed for matches only
+#[tauri::command]
+pub fn search_recent_files(query: String, case_sensitive: bool) -> Vec<RecentFile> {
+    let recent_path = match get_recent_file_path() {
+        Some(p) => p,
+        None => return vec![],
+    };
+
+    let recent_files = load_recent_files(&recent_path).unwrap_or_default();
+    let query = if case_sensitive { query } else { query.to_lowercase() };
+    let matches = |text: &str| {
+        if case_sensitive {
+            text.contains(&query)
+        } else {
+            text.to_lowercase().contains(&query)
+        }
+    };
+
+    let mut found = recent_files.into_iter()
+        .filter(|f| {
+            query.is_empty()
+                || matches(&f.name)
+                || f.alias.as_deref().is_some_and(matches)
+                || matches(&f.path)
+                || f.tags.iter().any(|t| matches(t))
+        })
+        .collect::<Vec<_>>();
+    refresh_recent_files(&mut found);
+    found
+}
+
+/// Remove all recent files whose path matches a glob pattern
+/// Returns the number of entries removed
+#[tauri::command]
+pub fn clear_recent_files_matching(state: State<'_, AppState>, path_glob: String) -> usize {
+    let pattern = match glob::Pattern::new(&path_glob) {
+        Ok(p) => p,
+        Err(_) => return 0,
+    };
+
+    with_recent_files(&state, |files| {
+        let before = files.len();
+        files.retain(|f| !pattern.matches(&f.path));
+        let removed = before - files.len();
+        (removed, removed > 0)
+    })
+    .unwrap_or(0)
+}
+
+// Cap on matches returned by a single all-matches search
+const MAX_SEARCH_MATCHES: usize = 1000;
+
+/// Line matcher shared by the pattern-based search commands
+enum LineMatcher {
+    Plain { needle: String, case_sensitive: bool },
+    Regex(Regex),
+}
+
+impl LineMatcher {
+    fn new(pattern: &str, use_regex: bool, case_sensitive: bool) -> Result<Self, String> {
+        if pattern.is_empty() {
+            return Err("Empty pattern".to_string());
+        }
+
+        if use_regex {
+            RegexBuilder::new(pattern)
+                .case_insensitive(!case_sensitive)
+                .build()
+                .map(LineMatcher::Regex)
+                .map_err(|e| format!("Invalid regex: {}", e))
+        } else if case_sensitive {
+            Ok(LineMatcher::Plain { needle: pattern.to_string(), case_sensitive })
+        } else {
+            Ok(LineMatcher::Plain { needle: pattern.to_lowercase(), case_sensitive })
+        }
+    }
+
+    fn is_match(&self, line: &str) -> bool {
+        match self {
+            LineMatcher::Plain { needle, case_sensitive: true } => line.contains(needle.as_str()),
+            LineMatcher::Plain { needle, case_sensitive: false } => {
+                line.to_lowercase().contains(needle.as_str())
+            }
+            LineMatcher::Regex(re) => re.is_match(line),
+        }
+    }
+}
+
+/// A single match with its surrounding context lines
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchMatch {
+    pub line_number: usize,
+    pub context_start_line: usize,
+    pub content: String,
+}
+
+/// Result for commands that return every match in a file
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AllMatchesResult {
+    pub success: bool,
+    pub matches: Vec<SearchMatch>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_lines: Option<usize>,
+    pub truncated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Stream a file and collect every matching line with context
+fn find_all_matches(path: &str, matcher: &LineMatcher, context_lines: usize) -> AllMatchesResult {
+    let failed = |error: &str| AllMatchesResult {
+        success: false,
+        matches: vec![],
+        total_lines: None,
+        truncated: false,
+        error: Some(error.to_string()),
+    };
+
+    let file = match File::open(path) {
+        Ok(f) => f,
+        Err(_) => return failed("Cannot read file"),
+    };
+
+    let mut reader = BufReader::new(file);
+    let mut before: VecDeque<String> = VecDeque::with_capacity(context_lines);
+    // Matches still collecting their after-context: (match, lines still needed)
+    let mut pending: Vec<(SearchMatch, usize)> = Vec::new();
+    let mut matches: Vec<SearchMatch> = Vec::new();
+    let mut truncated = false;
+    let mut total_lines = 0;
+    let mut buf = Vec::new();
+
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(_) => return failed("Cannot read file"),
+        }
+
+        total_lines += 1;
+        let line = String::from_utf8_lossy(trim_line_ending(&buf)).into_owned();
+
+        // Feed after-context to matches that still need it
+        for (m, remaining) in pending.iter_mut() {
+            m.content.push('\n');
+            m.content.push_str(&line);
+            *remaining -= 1;
+        }
+        let (done, still_pending): (Vec<_>, Vec<_>) = pending.into_iter().partition(|(_, r)| *r == 0);
+        matches.extend(done.into_iter().map(|(m, _)| m));
+        pending = still_pending;
+
+        if !truncated && matcher.is_match(&line) {
+            if matches.len() + pending.len() >= MAX_SEARCH_MATCHES {
+                truncated = true;
+            } else {
+                let mut content: Vec<&str> = before.iter().map(|l| l.as_str()).collect();
+                content.push(&line);
+                let m = SearchMatch {
+                    line_number: total_lines,
+                    context_start_line: total_lines - before.len(),
+                    content: content.join("\n"),
+                };
+                if context_lines > 0 {
+                    pending.push((m, context_lines));
+                } else {
+                    matches.push(m);
+                }
+            }
+        }
+
+        if context_lines > 0 {
+            if before.len() == context_lines {
+                before.pop_front();
+            }
+            before.push_back(line);
+        }
+    }
+
+    // Matches near the end of the file get whatever after-context exists
+    matches.extend(pending.into_iter().map(|(m, _)| m));
+
+    AllMatchesResult {
+        success: true,
+        matches,
+        total_lines: Some(total_lines),
+        truncated,
+        error: None,
+    }
+}
+
+/// A saved search pattern persisted in searches.json
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedSearch {
+    pub name: String,
+    pub pattern: String,
+    pub use_regex: bool,
+    pub case_sensitive: bool,
+    pub created_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_used_at: Option<i64>,
+    #[serde(default)]
+    pub use_count: u64,
+}
+
+/// Save a search pattern under a name, replacing any search with the same name
+#[tauri::command]
+pub fn save_search(name: String, pattern: String, use_regex: bool, case_sensitive: bool) -> bool {
+    if name.trim().is_empty() || LineMatcher::new(&pattern, use_regex, case_sensitive).is_err() {
+        return false;
+    }
+
+    let searches_path = match get_saved_searches_path() {
+        Some(p) => p,
+        None => return false,
+    };
+
+    let mut searches: Vec<SavedSearch> = load_json_list(&searches_path);
+    let existing = searches.iter().position(|s| s.name == name);
+
+    // Re-saving keeps the original creation time and usage stats
+    let (created_at, last_used_at, use_count) = match existing {
+        Some(i) => {
+            let old = searches.remove(i);
+            (old.created_at, old.last_used_at, old.use_count)
+        }
+        None => (Utc::now().timestamp_millis(), None, 0),
+    };
+
+    searches.push(SavedSearch {
+        name,
+        pattern,
+        use_regex,
+        case_sensitive,
+        created_at,
+        last_used_at,
+        use_count,
+    });
+
+    save_json_list(&searches_path, &searches)
+}
+
+/// Get all saved searches
+#[tauri::command]
+pub fn get_saved_searches() -> Vec<SavedSearch> {
+    match get_saved_searches_path() {
+        Some(p) => load_json_list(&p),
+        None => vec![],
+    }
+}
+
+/// Delete a saved search by name
+#[tauri::command]
+pub fn delete_saved_search(name: String) -> bool {
+    let searches_path = match get_saved_searches_path() {
+        Some(p) => p,
+        None => return false,
+    };
+
+    let mut searches: Vec<SavedSearch> = load_json_list(&searches_path);
+    let before = searches.len();
+    searches.retain(|s| s.name != name);
+
+    if searches.len() == before {
+        return false;
+    }
+
+    save_json_list(&searches_path, &searches)
+}
+
+/// Run a saved search against a file and record its usage
+#[tauri::command]
+pub fn apply_saved_search(name: String, path: String, context_lines: usize) -> AllMatchesResult {
+    let failed = |error: &str| AllMatchesResult {
+        success: false,
+        matches: vec![],
+        total_lines: None,
+        truncated: false,
+        error: Some(error.to_string()),
+    };
+
+    if path.is_empty() {
+        return failed("No path provided");
+    }
+
+    let searches_path = match get_saved_searches_path() {
+        Some(p) => p,
+        None => return failed("Saved search not found"),
+    };
+
+    let mut searches: Vec<SavedSearch> = load_json_list(&searches_path);
+    let search = match searches.iter_mut().find(|s| s.name == name) {
+        Some(s) => s,
+        None => return failed("Saved search not found"),
+    };
+
+    let matcher = match LineMatcher::new(&search.pattern, search.use_regex, search.case_sensitive) {
+        Ok(m) => m,
+        Err(e) => return failed(&e),
+    };
+
+    search.last_used_at = Some(Utc::now().timestamp_millis());
+    search.use_count += 1;
+    save_json_list(&searches_path, &searches);
+
+    find_all_matches(&path, &matcher, context_lines)
+}
+
+/// Changes to the recent files list since a given version
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentFilesDelta {
+    pub added: Vec<RecentFile>,
+    pub removed: Vec<String>,
+    pub updated: Vec<RecentFile>,
+    pub new_version: u64,
+    /// True when the base version is unknown and added holds the full list
+    pub reset: bool,
+}
+
+/// Get the changes to the recent files list since the given version
+/// Unknown or expired versions return the full list with reset set
+#[tauri::command]
+pub fn get_recent_files_since(state: State<'_, AppState>, version: u64) -> RecentFilesDelta {
+    let current = get_recent_file_path()
+        .and_then(|p| load_recent_files(&p))
+        .unwrap_or_default();
+
+    let (new_version, base) = match state.recent_history.lock() {
+        Ok(mut history) => {
+            if history.snapshots.is_empty() {
+                history.snapshots.push_back((0, current.clone()));
+            }
+            let base = history.snapshots.iter()
+                .find(|(v, _)| *v == version)
+                .map(|(_, files)| files.clone());
+            (history.version, base)
+        }
+        Err(_) => (0, None),
+    };
+
+    let base = match base {
+        Some(b) => b,
+        None => {
+            let mut added = current;
+            refresh_recent_files(&mut added);
+            return RecentFilesDelta {
+                added,
+                removed: vec![],
+                updated: vec![],
+                new_version,
+                reset: true,
+            };
+        }
+    };
+
+    let removed = base.iter()
+        .filter(|old| !current.iter().any(|f| f.path == old.path))
+        .map(|old| old.path.clone())
+        .collect();
+
+    let mut added = Vec::new();
+    let mut updated = Vec::new();
+    for f in current {
+        match base.iter().find(|old| old.path == f.path) {
+            None => added.push(f),
+            Some(old) if *old != f => updated.push(f),
+            Some(_) => {}
+        }
+    }
+    refresh_recent_files(&mut added);
+    refresh_recent_files(&mut updated);
+
+    RecentFilesDelta {
+        added,
+        removed,
+        updated,
+        new_version,
+        reset: false,
+    }
+}
+
+// Search history limits, per file and across all files
+const MAX_HISTORY_PER_FILE: usize = 50;
+const MAX_HISTORY_TOTAL: usize = 500;
+
+/// A single search recorded in search_history.json
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHistoryEntry {
+    pub query: String,
+    pub file_path: String,
+    pub use_regex: bool,
+    pub case_sensitive: bool,
+    pub timestamp: i64,
+    pub result_count: usize,
+}
+
+/// Append a search to the history, evicting the oldest entries over the limits
+/// Repeating a query for the same file moves it to the most recent position
+/// Only the in-memory list changes; flush_search_history writes it out on exit
+fn record_search_history(state: &AppState, entry: SearchHistoryEntry) {
+    let mut entries = match state.search_history.lock() {
+        Ok(e) => e,
+        Err(_) => return,
+    };
+
+    entries.retain(|e| !(e.file_path == entry.file_path && e.query == entry.query));
+
+    let file_path = entry.file_path.clone();
+    entries.push(entry);
+
+    let per_file = entries.iter().filter(|e| e.file_path == file_path).count();
+    if per_file > MAX_HISTORY_PER_FILE {
+        let mut excess = per_file - MAX_HISTORY_PER_FILE;
+        entries.retain(|e| {
+            if excess > 0 && e.file_path == file_path {
+                excess -= 1;
+                return false;
+            }
+            true
+        });
+    }
+
+    if entries.len() > MAX_HISTORY_TOTAL {
+        let excess = entries.len() - MAX_HISTORY_TOTAL;
+        entries.drain(..excess);
+    }
+
+    state.search_history_dirty.store(true, Ordering::Relaxed);
+}
+
+/// Write the search history to search_history.json if it changed since the last write
+/// Returns false if the write failed, leaving it pending for the next flush
+pub fn flush_search_history(state: &AppState) -> bool {
+    let entries = match state.search_history.lock() {
+        Ok(e) => e,
+        Err(_) => return false,
+    };
+    if !state.search_history_dirty.swap(false, Ordering::Relaxed) {
+        return true;
+    }
+
+    let saved = get_search_history_path().is_some_and(|p| save_json_list(&p, &entries));
+    if !saved {
+        state.search_history_dirty.store(true, Ordering::Relaxed);
+    }
+    saved
+}
+
+/// Get search history, newest first, for one file or for all files
+#[tauri::command]
+pub fn get_search_history(state: State<'_, AppState>, path: Option<String>) -> Vec<SearchHistoryEntry> {
+    let entries = match state.search_history.lock() {
+        Ok(e) => e,
+        Err(_) => return vec![],
+    };
+
+    entries.iter()
+        .rev()
+        .filter(|e| path.as_ref().map_or(true, |p| &e.file_path == p))
+        .cloned()
+        .collect()
+}
+
+/// Clear search history for one file, or all history when path is None
+#[tauri::command]
+pub fn clear_search_history(state: State<'_, AppState>, path: Option<String>) -> bool {
+    let history_path = match get_search_history_path() {
+        Some(p) => p,
+        None => return false,
+    };
+
+    let mut entries = match state.search_history.lock() {
+        Ok(e) => e,
+        Err(_) => return false,
+    };
+
+    match path {
+        Some(p) => entries.retain(|e| e.file_path != p),
+        None => entries.clear(),
+    }
+
+    let saved = save_json_list(&history_path, &entries);
+    state.search_history_dirty.store(!saved, Ordering::Relaxed);
+    saved
+}
+
+/// Result for detect_log_rotation command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RotationResult {
+    pub success: bool,
+    pub rotated: bool,
+    pub new_size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inode_changed: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Compare the file's inode with the one seen on the previous call
+/// None on the first call for a path and on platforms without inodes
+fn check_inode_changed(state: &AppState, path: &str, metadata: &fs::Metadata) -> Option<bool> {
+    let (inode, _) = file_identity(metadata)?;
+    let mut inodes = state.known_inodes.lock().ok()?;
+    inodes.insert(path.to_string(), inode).map(|old| old != inode)
+}
+
+/// Inode and device number identifying a file, or None on platforms without inodes
+#[cfg(unix)]
+fn file_identity(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some((metadata.ino(), metadata.dev()))
+}
+
+#[cfg(not(unix))]
+fn file_identity(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// Whether metadata describes a named pipe (FIFO)
+#[cfg(unix)]
+fn is_fifo(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    metadata.file_type().is_fifo()
+}
+
+#[cfg(not(unix))]
+fn is_fifo(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+/// Result for get_file_inode command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InodeResult {
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inode: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device: Option<u64>,
+    /// A named pipe, to be read with read_pipe rather than watched
+    pub is_fifo: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Get the inode and device of a file, which stay with the file across renames
+/// Pass the inode back to read_file to detect rotation that replaces the file
+#[tauri::command]
+pub fn get_file_inode(path: String) -> InodeResult {
+    let failed = |error: &str| InodeResult {
+        success: false,
+        inode: None,
+        device: None,
+        is_fifo: false,
+        error: Some(error.to_string()),
+    };
+
+    let metadata = match fs::metadata(&path) {
+        Ok(m) => m,
+        Err(_) => return failed("Cannot open file"),
+    };
+
+    match file_identity(&metadata) {
+        Some((inode, device)) => InodeResult {
+            success: true,
+            inode: Some(inode),
+            device: Some(device),
+            is_fifo: is_fifo(&metadata),
+            error: None,
+        },
+        None => failed("File inodes are not available on this platform"),
+    }
+}
+
+/// Polling check for log rotation: the file shrank below known_size or was replaced
+#[tauri::command]
+pub fn detect_log_rotation(state: State<'_, AppState>, path: String, known_size: u64) -> RotationResult {
+    let metadata = match fs::metadata(&path) {
+        Ok(m) => m,
+        Err(_) => {
+            return RotationResult {
+                success: false,
+                rotated: false,
+                new_size: 0,
+                inode_changed: None,
+                error: Some("Cannot open file".to_string()),
+            };
+        }
+    };
+
+    let new_size = metadata.len();
+    let inode_changed = check_inode_changed(&state, &path, &metadata);
+
+    RotationResult {
+        success: true,
+        rotated: new_size < known_size || inode_changed == Some(true),
+        new_size,
+        inode_changed,
+        error: None,
+    }
+}
+
+/// Result for raw (undecoded) file reads
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawTailResult {
+    pub success: bool,
+    /// Raw bytes, base64-encoded
+    pub data: String,
+    pub size: u64,
+    pub read_from: u64,
+    pub mtime: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mtime_iso: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl RawTailResult {
+    fn error(message: &str) -> Self {
+        RawTailResult {
+            success: false,
+            data: String::new(),
+            size: 0,
+            read_from: 0,
+            mtime: 0,
+            mtime_iso: None,
+            error: Some(message.to_string()),
+        }
+    }
+}
+
+/// Read up to max_bytes of raw bytes starting at read_from
+fn read_raw_bytes(path: &str, read_from: u64, max_bytes: u64) -> Result<Vec<u8>, String> {
+    let mut file = File::open(path).map_err(|_| "Cannot open file".to_string())?;
+    file.seek(SeekFrom::Start(read_from)).map_err(|_| "Cannot seek in file".to_string())?;
+
+    let mut bytes = Vec::new();
+    file.take(max_bytes)
+        .read_to_end(&mut bytes)
+        .map_err(|_| "Cannot read file".to_string())?;
+    Ok(bytes)
+}
+
+/// Read the last num_bytes of a file without any text decoding
+/// Lets the frontend apply its own decoder for proprietary encodings
+#[tauri::command]
+pub fn get_file_tail_raw(path: String, num_bytes: u64) -> RawTailResult {
+    if path.is_empty() {
+        return RawTailResult::error("No path provided");
+    }
+
+    let metadata = match fs::metadata(&path) {
+        Ok(m) => m,
+        Err(_) => return RawTailResult::error("Cannot open file"),
+    };
+
+    let size = metadata.len();
+    let num_bytes = num_bytes.min(MAX_READ_SIZE);
+    let read_from = size.saturating_sub(num_bytes);
+
+    match read_raw_bytes(&path, read_from, num_bytes) {
+        Ok(bytes) => RawTailResult {
+            success: true,
+            data: BASE64.encode(&bytes),
+            size,
+            read_from,
+            mtime: mtime_millis(&metadata).unwrap_or(0),
+            mtime_iso: mtime_millis(&metadata).and_then(mtime_iso),
+            error: None,
+        },
+        Err(e) => RawTailResult::error(&e),
+    }
+}
+
+/// Raw polling counterpart of get_file_tail_raw: bytes appended since offset
+/// If the file shrank below offset it was replaced, so reading restarts at 0
+#[tauri::command]
+pub fn get_file_change_since(path: String, offset: u64) -> RawTailResult {
+    if path.is_empty() {
+        return RawTailResult::error("No path provided");
+    }
+
+    let metadata = match fs::metadata(&path) {
+        Ok(m) => m,
+        Err(_) => return RawTailResult::error("Cannot open file"),
+    };
+
+    let size = metadata.len();
+    let read_from = if size < offset { 0 } else { offset };
+
+    match read_raw_bytes(&path, read_from, MAX_READ_SIZE) {
+        Ok(bytes) => RawTailResult {
+            success: true,
+            data: BASE64.encode(&bytes),
+            size,
+            read_from,
+            mtime: mtime_millis(&metadata).unwrap_or(0),
+            mtime_iso: mtime_millis(&metadata).and_then(mtime_iso),
+            error: None,
+        },
+        Err(e) => RawTailResult::error(&e),
+    }
+}
+
+// Chunk size for backward scans from the end of a file
+const REVERSE_CHUNK_SIZE: u64 = 64 * 1024;
+
+/// Reads a file's lines from last to first in fixed-size chunks
+/// Lines are only decoded once complete, so multi-byte UTF-8 sequences
+/// split across chunk boundaries are never mangled
+struct ReverseLineReader {
+    file: File,
+    /// File offset of the first byte held in buf
+    pos: u64,
+    /// Bytes read from the file but not yet returned as lines
+    buf: Vec<u8>,
+    bytes_scanned: u64,
+    done: bool,
+}
+
+impl ReverseLineReader {
+    fn open(path: &str) -> std::io::Result<Self> {
+        let file = File::open(path)?;
+        let size = file.metadata()?.len();
+        Self::from_file(file, size)
+    }
+
+    /// Reader over the first size bytes of a file, for callers that already took a
+    /// size snapshot and must not see bytes appended after it
+    fn open_with_size(path: &str, size: u64) -> std::io::Result<Self> {
+        Self::from_file(File::open(path)?, size)
+    }
+
+    fn from_file(mut file: File, size: u64) -> std::io::Result<Self> {
+        let mut end = size;
+
+        // A trailing newline terminates the last line rather than starting an empty one
+        if end > 0 {
+            let mut last = [0u8; 1];
+            file.seek(SeekFrom::Start(end - 1))?;
+            file.read_exact(&mut last)?;
+            if last[0] == b'\n' {
+                end -= 1;
+            }
+        }
+
+        Ok(ReverseLineReader {
+            file,
+            pos: end,
+            buf: Vec::new(),
+            bytes_scanned: 0,
+            done: end == 0,
+        })
+    }
+
+    /// Whether every line back to the start of the file has been returned
+    fn reached_start(&self) -> bool {
+        self.done
+    }
+
+    /// Next line walking backwards, with its starting byte offset
+    fn next_line(&mut self) -> std::io::Result<Option<(u64, Vec<u8>)>> {
+        loop {
+            if self.done {
+                return Ok(None);
+            }
+
+            if let Some(i) = self.buf.iter().rposition(|&b| b == b'\n') {
+                let line = self.buf.split_off(i + 1);
+                self.buf.truncate(i);
+                let start = self.pos + i as u64 + 1;
+                return Ok(Some((start, trim_line_ending(&line).to_vec())));
+            }
+
+            if self.pos == 0 {
+                // What's left is the first line of the file
+                self.done = true;
+                let line = std::mem::take(&mut self.buf);
+                return Ok(Some((0, trim_line_ending(&line).to_vec())));
+            }
+
+            let chunk_start = self.pos.saturating_sub(REVERSE_CHUNK_SIZE);
+            let mut chunk = vec![0u8; (self.pos - chunk_start) as usize];
+            self.file.seek(SeekFrom::Start(chunk_start))?;
+            self.file.read_exact(&mut chunk)?;
+            self.bytes_scanned += chunk.len() as u64;
+
+            chunk.extend_from_slice(&self.buf);
+            self.buf = chunk;
+            self.pos = chunk_start;
+        }
+    }
+}
+
+/// Count the newlines in the first len bytes of a file
+fn count_newlines_before(path: &str, len: u64) -> std::io::Result<usize> {
+    let file = File::open(path)?;
+    let mut reader = BufReader::with_capacity(COUNT_BUFFER_SIZE, file.take(len));
+    let mut count = 0;
+    loop {
+        let chunk = reader.fill_buf()?;
+        if chunk.is_empty() {
+            return Ok(count);
+        }
+        count += memchr::memchr_iter(b'\n', chunk).count();
+        let consumed = chunk.len();
+        reader.consume(consumed);
+    }
+}
+
+/// Call f with every line of a file (line ending trimmed), returning the line count
+/// Reads COUNT_BUFFER_SIZE chunks; only a line split across two chunks is copied
+fn for_each_line(file: File, mut f: impl FnMut(&[u8])) -> std::io::Result<u64> {
+    let mut reader = BufReader::with_capacity(COUNT_BUFFER_SIZE, file);
+    let mut partial = Vec::new();
+    let mut lines = 0;
+    loop {
+        let chunk = reader.fill_buf()?;
+        if chunk.is_empty() {
+            break;
+        }
+        let mut start = 0;
+        for end in memchr::memchr_iter(b'\n', chunk) {
+            if partial.is_empty() {
+                f(trim_line_ending(&chunk[start..=end]));
+            } else {
+                partial.extend_from_slice(&chunk[start..=end]);
+                f(trim_line_ending(&partial));
+                partial.clear();
+            }
+            lines += 1;
+            start = end + 1;
+        }
+        partial.extend_from_slice(&chunk[start..]);
+        let consumed = chunk.len();
+        reader.consume(consumed);
+    }
+    if !partial.is_empty() {
+        f(trim_line_ending(&partial));
+        lines += 1;
+    }
+    Ok(lines)
+}
+
+/// Read the last max_lines lines of a file, starting exactly at a line boundary
+/// max_bytes (default MAX_READ_SIZE) bounds the read; lines beyond it are dropped
+/// start_line is only set when a cached line index already covers the file
+#[tauri::command]
+pub fn get_file_tail_with_context(
+    state: State<'_, AppState>,
+    path: String,
+    max_lines: usize,
+    max_bytes: Option<u64>,
+) -> FileResult {
+    if path.is_empty() {
+        return FileResult::error("No path provided");
+    }
+
+    let metadata = match fs::metadata(&path) {
+        Ok(m) => m,
+        Err(_) => return FileResult::error("Cannot open file"),
+    };
+    let size = metadata.len();
+    let max_bytes = max_bytes.unwrap_or(MAX_READ_SIZE);
+
+    // Use the same size snapshot throughout so lines appended meanwhile are ignored
+    let mut reader = match ReverseLineReader::open_with_size(&path, size) {
+        Ok(r) => r,
+        Err(_) => return FileResult::error("Cannot open file"),
+    };
+
+    // Walk back line by line to find where the returned tail starts
+    let mut start = size;
+    let mut lines_returned = 0;
+    while lines_returned < max_lines {
+        match reader.next_line() {
+            Ok(Some((line_start, _))) => {
+                if size.saturating_sub(line_start) > max_bytes {
+                    break;
+                }
+                start = line_start;
+                lines_returned += 1;
+            }
+            Ok(None) => break,
+            Err(_) => return FileResult::error("Cannot read file"),
+        }
+    }
+
+    let content = match read_range_lossy(&path, start, size - start) {
+        Ok(c) => c,
+        Err(e) => return FileResult::error(e),
+    };
+    let replacement_char_count = count_replacements(&content);
+
+    // Counting the lines before start would scan the whole file, which a tail read avoids
+    let start_line = cached_line_number(&state, &path, &metadata, start);
+
+    FileResult {
+        success: true,
+        content: Some(content),
+        path: Some(path.clone()),
+        name: Some(get_filename(&path)),
+        display_name: get_filename(&path),
+        size: Some(size),
+        read_from_offset: start,
+        next_offset: size,
+        mtime: mtime_millis(&metadata),
+        mtime_iso: mtime_millis(&metadata).and_then(mtime_iso),
+        truncated: Some(start > 0),
+        lines_returned: Some(lines_returned),
+        start_line,
+        replacement_char_count: Some(replacement_char_count),
+        error: encoding_warning(replacement_char_count),
+        ..Default::default()
+    }
+}
+
+/// Result for get_file_tail_offset command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TailOffsetResult {
+    pub success: bool,
+    pub byte_offset: u64,
+    /// 1-indexed line at byte_offset, extrapolated from the scanned tail unless it is 1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_line_estimate: Option<u64>,
+    pub file_size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Byte offset where the last lines_from_end lines start, found by scanning backwards
+/// Pass it as read_file's offset to get line-aligned content
+#[tauri::command]
+pub fn get_file_tail_offset(path: String, lines_from_end: usize) -> TailOffsetResult {
+    let failed = |error: &str| TailOffsetResult {
+        success: false,
+        byte_offset: 0,
+        start_line_estimate: None,
+        file_size: 0,
+        error: Some(error.to_string()),
+    };
+
+    let mut file = match File::open(&path) {
+        Ok(f) => f,
+        Err(_) => return failed("Cannot open file"),
+    };
+    let file_size = match file.metadata() {
+        Ok(m) => m.len(),
+        Err(_) => return failed("Cannot read file metadata"),
+    };
+
+    let mut buf = vec![0u8; REVERSE_CHUNK_SIZE as usize];
+    let mut pos = file_size;
+    let mut newlines_seen: u64 = 0;
+    let mut byte_offset = None;
+    // A trailing newline ends the last line rather than starting another one
+    let mut skip_trailing = true;
+
+    while pos > 0 && byte_offset.is_none() && lines_from_end > 0 {
+        let chunk_start = pos.saturating_sub(REVERSE_CHUNK_SIZE);
+        let chunk = &mut buf[..(pos - chunk_start) as usize];
+        if file.seek(SeekFrom::Start(chunk_start)).is_err() || file.read_exact(chunk).is_err() {
+            return failed("Cannot read file");
+        }
+
+        for i in memchr::memrchr_iter(b'\n', chunk) {
+            if skip_trailing && chunk_start + i as u64 == file_size - 1 {
+                continue;
+            }
+            newlines_seen += 1;
+            if newlines_seen == lines_from_end as u64 {
+                byte_offset = Some(chunk_start + i as u64 + 1);
+                break;
+            }
+        }
+        skip_trailing = false;
+        pos = chunk_start;
+    }
+
+    let (byte_offset, start_line_estimate) = match byte_offset {
+        Some(offset) => {
+            // Assume the rest of the file has the same line density as the scanned tail
+            let scanned = (file_size - offset).max(1) as f64;
+            let lines_before = offset as f64 * lines_from_end as f64 / scanned;
+            (offset, Some(lines_before.round() as u64 + 1))
+        }
+        None if lines_from_end == 0 => (file_size, None),
+        // Fewer lines than asked for: the whole file
+        None => (0, Some(1)),
+    };
+
+    TailOffsetResult {
+        success: true,
+        byte_offset,
+        start_line_estimate,
+        file_size,
+        error: None,
+    }
+}
+
+/// Result for read_file_reverse command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReverseReadResult {
+    pub success: bool,
+    pub lines: Vec<String>,
+    pub total_returned: usize,
+    pub reached_start: bool,
+    pub bytes_scanned: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Read up to max_lines from the end of a file, last line first
+#[tauri::command]
+pub fn read_file_reverse(path: String, max_lines: usize) -> ReverseReadResult {
+    let failed = |error: &str| ReverseReadResult {
+        success: false,
+        lines: vec![],
+        total_returned: 0,
+        reached_start: false,
+        bytes_scanned: 0,
+        error: Some(error.to_string()),
+    };
+
+    if path.is_empty() {
+        return failed("No path provided");
+    }
+
+    let mut reader = match ReverseLineReader::open(&path) {
+        Ok(r) => r,
+        Err(_) => return failed("Cannot open file"),
+    };
+
+    let mut lines = Vec::new();
+    while lines.len() < max_lines {
+        match reader.next_line() {
+            Ok(Some((_, line))) => lines.push(String::from_utf8_lossy(&line).into_owned()),
+            Ok(None) => break,
+            Err(_) => return failed("Cannot read file"),
+        }
+    }
+
+    ReverseReadResult {
+        success: true,
+        total_returned: lines.len(),
+        lines,
+        reached_start: reader.reached_start(),
+        bytes_scanned: reader.bytes_scanned,
+        error: None,
+    }
+}
+
+/// Parse a strptime-style timestamp at the start of a line into Unix millis (UTC)
+/// Falls back to skipping a leading "[" so bracketed timestamps also parse
+fn parse_line_timestamp(line: &str, pattern: &str) -> Option<i64> {
+    split_line_timestamp(line, pattern).map(|(ts, _)| ts)
+}
+
+/// parse_line_timestamp, also returning the rest of the line after the timestamp
+fn split_line_timestamp<'a>(line: &'a str, pattern: &str) -> Option<(i64, &'a str)> {
+    let line = line.trim_start();
+    NaiveDateTime::parse_and_remainder(line, pattern)
+        .or_else(|e| match line.strip_prefix('[') {
+            Some(rest) => NaiveDateTime::parse_and_remainder(rest, pattern),
+            None => Err(e),
+        })
+        .ok()
+        .map(|(ts, rest)| (ts.and_utc().timestamp_millis(), rest))
+}
+
+/// Result for merge_log_files command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeResult {
+    pub success: bool,
+    pub lines_merged: u64,
+    pub files_processed: usize,
+    pub unparseable_lines: u64,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// One input of a merge, with the timestamp of its last parseable line
+struct MergeSource {
+    reader: BufReader<File>,
+    last_ts: i64,
+}
+
+impl MergeSource {
+    /// Next line and its timestamp; lines without one inherit the previous line's
+    fn next_line(&mut self, pattern: &str, unparseable: &mut u64) -> std::io::Result<Option<(i64, String)>> {
+        let mut buf = Vec::new();
+        if self.reader.read_until(b'\n', &mut buf)? == 0 {
+            return Ok(None);
+        }
+
+        let line = String::from_utf8_lossy(trim_line_ending(&buf)).into_owned();
+        match parse_line_timestamp(&line, pattern) {
+            Some(ts) => self.last_ts = ts,
+            None => *unparseable += 1,
+        }
+        Ok(Some((self.last_ts, line)))
+    }
+}
+
+/// Interleave several log files by timestamp and write the result to output_path
+/// Each input is streamed, so memory use is one line per file
+/// output_path is replaced atomically and must not be one of the inputs
+#[tauri::command]
+pub fn merge_log_files(paths: Vec<String>, timestamp_pattern: String, output_path: String) -> MergeResult {
+    let started = Instant::now();
+    let failed = |error: String| MergeResult {
+        success: false,
+        lines_merged: 0,
+        files_processed: 0,
+        unparseable_lines: 0,
+        duration_ms: started.elapsed().as_millis() as u64,
+        error: Some(error),
+    };
+
+    if paths.is_empty() || timestamp_pattern.is_empty() || output_path.is_empty() {
+        return failed("Invalid parameters".to_string());
+    }
+
+    let mut sources = Vec::with_capacity(paths.len());
+    for path in &paths {
+        match File::open(path) {
+            Ok(f) => sources.push(MergeSource {
+                reader: BufReader::new(f),
+                last_ts: i64::MIN,
+            }),
+            Err(_) => return failed(format!("Cannot open file: {}", path)),
+        }
+    }
+
+    // Replacing an input with the merge output would lose it; an output that doesn't
+    // exist yet can't be one of the inputs, which were all opened above
+    if let Ok(output) = fs::canonicalize(&output_path) {
+        if paths.iter().any(|p| fs::canonicalize(p).is_ok_and(|p| p == output)) {
+            return failed("Output file is one of the files being merged".to_string());
+        }
+    }
+
+    let temp = match temp_file_beside(&output_path) {
+        Ok(t) => t,
+        Err(e) => return failed(format!("Cannot write file: {}", e)),
+    };
+    let mut writer = BufWriter::new(temp);
+
+    let mut unparseable_lines = 0;
+    let mut lines_merged = 0;
+
+    // Min-heap on (timestamp, file index); ties keep the order of paths
+    let mut heads: Vec<Option<String>> = vec![None; sources.len()];
+    let mut heap = BinaryHeap::new();
+
+    for (idx, source) in sources.iter_mut().enumerate() {
+        match source.next_line(&timestamp_pattern, &mut unparseable_lines) {
+            Ok(Some((ts, line))) => {
+                heads[idx] = Some(line);
+                heap.push(Reverse((ts, idx)));
+            }
+            Ok(None) => {}
+            Err(_) => return failed(format!("Cannot read file: {}", paths[idx])),
+        }
+    }
+
+    while let Some(Reverse((_, idx))) = heap.pop() {
+        if let Some(line) = heads[idx].take() {
+            if writeln!(writer, "{}", line).is_err() {
+                return failed("Cannot write file".to_string());
+            }
+            lines_merged += 1;
+        }
+
+        match sources[idx].next_line(&timestamp_pattern, &mut unparseable_lines) {
+            Ok(Some((ts, line))) => {
+                heads[idx] = Some(line);
+                heap.push(Reverse((ts, idx)));
+            }
+            Ok(None) => {}
+            Err(_) => return failed(format!("Cannot read file: {}", paths[idx])),
+        }
+    }
+
+    let temp = match writer.into_inner() {
+        Ok(t) => t,
+        Err(_) => return failed("Cannot write file".to_string()),
+    };
+    if let Err(e) = temp.persist(&output_path) {
+        return failed(format!("Cannot write file: {}", e.error));
+    }
+
+    MergeResult {
+        success: true,
+        lines_merged,
+        files_processed: sources.len(),
+        unparseable_lines,
+        duration_ms: started.elapsed().as_millis() as u64,
+        error: None,
+    }
+}
+
+// Cap on entries returned by the structured log parsers
+const MAX_PARSED_ENTRIES: usize = 10_000;
+
+/// Parse one NDJSON line into its top-level object, if it is one
+fn parse_json_line(line: &str) -> Option<serde_json::Map<String, serde_json::Value>> {
+    match serde_json::from_str::<serde_json::Value>(line.trim()) {
+        Ok(serde_json::Value::Object(obj)) => Some(obj),
+        _ => None,
+    }
+}
+
+/// Result for parse_json_log command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedLogResult {
+    pub success: bool,
+    pub entries: Vec<HashMap<String, serde_json::Value>>,
+    pub total_lines: usize,
+    pub parsed_lines: usize,
+    pub failed_lines: usize,
+    pub truncated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Stream an NDJSON log and extract the requested fields from each line
+/// An empty field_names returns every top-level field; lines that aren't JSON
+/// objects come back as {"_raw": line}. filter_where keeps only entries whose
+/// fields equal all of the given values
+#[tauri::command]
+pub fn parse_json_log(
+    path: String,
+    field_names: Vec<String>,
+    filter_where: Option<HashMap<String, serde_json::Value>>,
+) -> ParsedLogResult {
+    let failed = |error: &str| ParsedLogResult {
+        success: false,
+        entries: vec![],
+        total_lines: 0,
+        parsed_lines: 0,
+        failed_lines: 0,
+        truncated: false,
+        error: Some(error.to_string()),
+    };
+
+    let file = match File::open(&path) {
+        Ok(f) => f,
+        Err(_) => return failed("Cannot open file"),
+    };
+
+    let mut reader = BufReader::new(file);
+    let mut entries = Vec::new();
+    let mut total_lines = 0;
+    let mut parsed_lines = 0;
+    let mut failed_lines = 0;
+    let mut truncated = false;
+    let mut buf = Vec::new();
+
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(_) => return failed("Cannot read file"),
+        }
+
+        total_lines += 1;
+        let line = String::from_utf8_lossy(trim_line_ending(&buf));
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let entry = match parse_json_line(&line) {
+            Some(obj) => {
+                parsed_lines += 1;
+
+                let matches_filter = filter_where.as_ref().map_or(true, |filter| {
+                    filter.iter().all(|(k, v)| obj.get(k) == Some(v))
+                });
+                if !matches_filter {
+                    continue;
+                }
+
+                if field_names.is_empty() {
+                    obj.into_iter().collect()
+                } else {
+                    field_names.iter()
+                        .filter_map(|name| obj.get(name).map(|v| (name.clone(), v.clone())))
+                        .collect()
+                }
+            }
+            None => {
+                failed_lines += 1;
+
+                // Raw lines have no fields, so they can never satisfy a filter
+                if filter_where.is_some() {
+                    continue;
+                }
+
+                let mut raw = HashMap::new();
+                raw.insert("_raw".to_string(), serde_json::Value::String(line.into_owned()));
+                raw
+            }
+        };
+
+        if entries.len() < MAX_PARSED_ENTRIES {
+            entries.push(entry);
+        } else {
+            truncated = true;
+        }
+    }
+
+    ParsedLogResult {
+        success: true,
+        entries,
+        total_lines,
+        parsed_lines,
+        failed_lines,
+        truncated,
+        error: None,
+    }
+}
+
+// Default labels for get_log_level_counts (case-insensitive substring matches)
+const DEFAULT_LEVEL_LABELS: [&str; 6] = ["ERROR", "WARN", "WARNING", "INFO", "DEBUG", "TRACE"];
+
+/// Result for get_log_level_counts command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogLevelCounts {
+    pub success: bool,
+    pub counts: HashMap<String, u64>,
+    pub total_lines: u64,
+    pub unmatched_lines: u64,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Count lines per log level in a single streaming pass
+/// patterns maps user-defined labels to regexes; by default the common
+/// level names are matched as case-insensitive substrings
+#[tauri::command]
+pub fn get_log_level_counts(path: String, patterns: Option<HashMap<String, String>>) -> LogLevelCounts {
+    let started = Instant::now();
+    let failed = |error: String| LogLevelCounts {
+        success: false,
+        counts: HashMap::new(),
+        total_lines: 0,
+        unmatched_lines: 0,
+        duration_ms: started.elapsed().as_millis() as u64,
+        error: Some(error),
+    };
+
+    // User patterns run as one RegexSet; the default labels are searched for in a
+    // lowercased copy of the line, so neither costs an allocation per label
+    let (labels, pattern_set, label_finders) = match patterns {
+        Some(patterns) => {
+            let (labels, patterns): (Vec<String>, Vec<String>) = patterns.into_iter().unzip();
+            for (label, pattern) in labels.iter().zip(&patterns) {
+                if pattern.is_empty() {
+                    return failed(format!("Empty pattern ({})", label));
+                }
+                if let Err(e) = regex::bytes::Regex::new(pattern) {
+                    return failed(format!("Invalid regex: {} ({})", e, label));
+                }
+            }
+            match regex::bytes::RegexSet::new(&patterns) {
+                Ok(set) => (labels, Some(set), Vec::new()),
+                Err(e) => return failed(format!("Invalid regex: {}", e)),
+            }
+        }
+        None => {
+            let finders = DEFAULT_LEVEL_LABELS.iter()
+                .map(|label| memchr::memmem::Finder::new(label.to_ascii_lowercase().as_bytes()).into_owned())
+                .collect();
+            (DEFAULT_LEVEL_LABELS.iter().map(|l| l.to_string()).collect(), None, finders)
+        }
+    };
+
+    let file = match File::open(&path) {
+        Ok(f) => f,
+        Err(_) => return failed("Cannot open file".to_string()),
+    };
+
+    let mut label_counts = vec![0u64; labels.len()];
+    let mut unmatched_lines = 0;
+    let mut lowered = Vec::new();
+
+    let scanned = for_each_line(file, |line| {
+        let mut matched = false;
+        match &pattern_set {
+            Some(set) => {
+                if set.is_match(line) {
+                    matched = true;
+                    for i in set.matches(line).iter() {
+                        label_counts[i] += 1;
+                    }
+                }
+            }
+            None => {
+                lowered.clear();
+                lowered.extend_from_slice(line);
+                lowered.make_ascii_lowercase();
+                for (i, finder) in label_finders.iter().enumerate() {
+                    if finder.find(&lowered).is_some() {
+                        matched = true;
+                        label_counts[i] += 1;
+                    }
+                }
+            }
+        }
+        if !matched {
+            unmatched_lines += 1;
+        }
+    });
+    let total_lines = match scanned {
+        Ok(n) => n,
+        Err(_) => return failed("Cannot read file".to_string()),
+    };
+    let counts = labels.into_iter().zip(label_counts).collect();
+
+    LogLevelCounts {
+        success: true,
+        counts,
+        total_lines,
+        unmatched_lines,
+        duration_ms: started.elapsed().as_millis() as u64,
+        error: None,
+    }
+}
+
+// Number of previous sessions kept besides last.json
+const MAX_SESSION_SLOTS: u8 = 5;
+
+/// Viewer state persisted across restarts
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionState {
+    pub file_path: String,
+    pub byte_offset: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_query: Option<String>,
+    #[serde(default)]
+    pub pinned_files: Vec<String>,
+    pub opened_at: i64,
+}
+
+/// Summary of a saved session slot
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionMeta {
+    pub slot: u8,
+    pub file_path: String,
+    pub opened_at: i64,
+}
+
+/// Slot 0 is last.json; slots 1..=MAX_SESSION_SLOTS are older sessions, newest first
+fn session_slot_path(dir: &Path, slot: u8) -> PathBuf {
+    if slot == 0 {
+        dir.join("last.json")
+    } else {
+        dir.join(format!("session_{}.json", slot))
+    }
+}
+
+fn load_session(path: &Path) -> Option<SessionState> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+}
+
+/// Save the current session to last.json, shifting older sessions down a slot
+#[tauri::command]
+pub fn session_save(session: SessionState) -> bool {
+    let dir = match get_sessions_dir() {
+        Some(d) => d,
+        None => return false,
+    };
+
+    if fs::create_dir_all(&dir).is_err() {
+        return false;
+    }
+
+    let json = match serde_json::to_string_pretty(&session) {
+        Ok(j) => j,
+        Err(_) => return false,
+    };
+
+    // Write the new session out before touching the slots, so a failed write
+    // leaves the saved sessions as they were
+    let last = session_slot_path(&dir, 0);
+    let mut temp = match temp_file_beside(&last) {
+        Ok(t) => t,
+        Err(_) => return false,
+    };
+    if temp.write_all(json.as_bytes()).is_err() {
+        return false;
+    }
+
+    // Oldest slot falls off; the rest move down one (missing slots are skipped).
+    // last.json is copied rather than moved so it exists until replaced below
+    let _ = fs::remove_file(session_slot_path(&dir, MAX_SESSION_SLOTS));
+    for slot in (1..MAX_SESSION_SLOTS).rev() {
+        let from = session_slot_path(&dir, slot);
+        if from.exists() {
+            let _ = fs::rename(&from, session_slot_path(&dir, slot + 1));
+        }
+    }
+    if last.exists() {
+        let _ = fs::copy(&last, session_slot_path(&dir, 1));
+    }
+
+    temp.persist(&last).is_ok()
+}
+
+/// Restore a saved session: the last one by default, or an older numbered slot
+#[tauri::command]
+pub fn session_restore(slot: Option<u8>) -> Option<SessionState> {
+    let slot = slot.unwrap_or(0);
+    if slot > MAX_SESSION_SLOTS {
+        return None;
+    }
+
+    let dir = get_sessions_dir()?;
+    load_session(&session_slot_path(&dir, slot))
+}
+
+/// List the available session slots
+#[tauri::command]
+pub fn get_sessions() -> Vec<SessionMeta> {
+    let dir = match get_sessions_dir() {
+        Some(d) => d,
+        None => return vec![],
+    };
+
+    (0..=MAX_SESSION_SLOTS)
+        .filter_map(|slot| {
+            load_session(&session_slot_path(&dir, slot)).map(|s| SessionMeta {
+                slot,
+                file_path: s.file_path,
+                opened_at: s.opened_at,
+            })
+        })
+        .collect()
+}
+
+/// Result for read_log_sequence command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogSequenceResult {
+    pub success: bool,
+    pub files_found: Vec<String>,
+    pub total_bytes_read: u64,
+    pub content: String,
+    /// True when reading stopped at MAX_READ_SIZE before the end of the sequence
+    pub truncated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Rotation index of a sibling named <base>.<N> or <base>.<N>.gz
+fn rotation_index(file_name: &str, base_name: &str) -> Option<u32> {
+    let suffix = file_name.strip_prefix(base_name)?.strip_prefix('.')?;
+    let number = suffix.strip_suffix(".gz").unwrap_or(suffix);
+    number.parse().ok()
+}
+
+/// Read up to limit bytes of a log file, decompressing .gz files on the fly
+/// A .gz file may hold several gzip members (e.g. appended by rotation); all are read
+fn read_log_file_bytes(path: &Path, limit: u64) -> std::io::Result<Vec<u8>> {
+    let file = File::open(path)?;
+    let mut bytes = Vec::new();
+    if path.extension().is_some_and(|ext| ext == "gz") {
+        MultiGzDecoder::new(file).take(limit).read_to_end(&mut bytes)?;
+    } else {
+        BufReader::new(file).take(limit).read_to_end(&mut bytes)?;
+    }
+    Ok(bytes)
+}
+
+/// Read a rotated log sequence (app.log.2.gz, app.log.1, app.log) as one stream
+/// Files are read oldest first, each preceded by a "--- <filename> ---" line
+/// Reading stops once MAX_READ_SIZE bytes have been read in total
+#[tauri::command]
+pub fn read_log_sequence(base_path: String, max_files: usize) -> LogSequenceResult {
+    let failed = |error: &str| LogSequenceResult {
+        success: false,
+        files_found: vec![],
+        total_bytes_read: 0,
+        content: String::new(),
+        truncated: false,
+        error: Some(error.to_string()),
+    };
+
+    if base_path.is_empty() {
+        return failed("No path provided");
+    }
+
+    let base = Path::new(&base_path);
+    let base_name = get_filename(&base_path);
+    let dir = match base.parent() {
+        Some(d) if !d.as_os_str().is_empty() => d,
+        _ => Path::new("."),
+    };
+
+    let entries = match fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(_) => return failed("Cannot read directory"),
+    };
+
+    // Newest first: the live file, then .1, .2, ...
+    let mut rotated: Vec<(u32, PathBuf)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let index = rotation_index(&entry.file_name().to_string_lossy(), &base_name)?;
+            Some((index, entry.path()))
+        })
+        .collect();
+    rotated.sort_by_key(|(index, _)| *index);
+
+    let mut sequence: Vec<PathBuf> = Vec::new();
+    if base.is_file() {
+        sequence.push(base.to_path_buf());
+    }
+    sequence.extend(rotated.into_iter().map(|(_, path)| path));
+    sequence.truncate(max_files);
+    sequence.reverse();
+
+    if sequence.is_empty() {
+        return failed("No log files found");
+    }
+
+    let mut content = String::new();
+    let mut files_found = Vec::new();
+    let mut total_bytes_read: u64 = 0;
+    let mut truncated = false;
+
+    for path in &sequence {
+        let remaining = MAX_READ_SIZE - total_bytes_read;
+        // One byte past the budget tells whether anything was left unread
+        let mut bytes = match read_log_file_bytes(path, remaining + 1) {
+            Ok(b) => b,
+            Err(_) => return failed(&format!("Cannot read {}", path.display())),
+        };
+        if bytes.len() as u64 > remaining {
+            bytes.truncate(remaining as usize);
+            truncated = true;
+            if bytes.is_empty() {
+                break;
+            }
+        }
+
+        let name = get_filename(&path.to_string_lossy());
+        content.push_str(&format!("--- {} ---\n", name));
+        content.push_str(&String::from_utf8_lossy(&bytes));
+        if !content.ends_with('\n') {
+            content.push('\n');
+        }
+
+        total_bytes_read += bytes.len() as u64;
+        files_found.push(path.to_string_lossy().to_string());
+        if truncated {
+            break;
+        }
+    }
+
+    LogSequenceResult {
+        success: true,
+        files_found,
+        total_bytes_read,
+        content,
+        truncated,
+        error: None,
+    }
+}
+
+/// inotify limits and this app's usage of them; the limits are None off Linux
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InotifyStats {
+    /// Events the kernel queues per instance before dropping them
+    pub max_queued_events: Option<u64>,
+    /// Watches allowed per user, shared with every other process
+    pub max_user_watches: Option<u64>,
+    /// Active file, directory and tail-follow watchers in this app
+    pub current_watches: usize,
+    pub platform: String,
+}
+
+/// Read a numeric inotify limit from /proc/sys/fs/inotify
+#[cfg(target_os = "linux")]
+fn read_inotify_limit(name: &str) -> Option<u64> {
+    fs::read_to_string(Path::new("/proc/sys/fs/inotify").join(name))
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_inotify_limit(_name: &str) -> Option<u64> {
+    None
+}
+
+fn inotify_stats(state: &AppState) -> InotifyStats {
+    let file_watches = state.watchers.lock().map(|w| w.len()).unwrap_or(0);
+    let directory_watches = state.directory_watchers.lock().map(|w| w.len()).unwrap_or(0);
+    let tail_watches = state.tail_followers.lock().map(|f| f.len()).unwrap_or(0);
+
+    InotifyStats {
+        max_queued_events: read_inotify_limit("max_queued_events"),
+        max_user_watches: read_inotify_limit("max_user_watches"),
+        current_watches: file_watches + directory_watches + tail_watches,
+        platform: std::env::consts::OS.to_string(),
+    }
+}
+
+/// inotify limits, to explain missed file changes when the kernel queue overflows
+#[tauri::command]
+pub fn get_inotify_stats(state: State<'_, AppState>) -> InotifyStats {
+    inotify_stats(&state)
+}
+
+/// Environment and state snapshot attached to bug reports
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticsReport {
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub home_dir: Option<String>,
+    pub config_dir: Option<String>,
+    pub config_exists: bool,
+    pub recent_file_count: usize,
+    pub recent_file_parse_error: Option<String>,
+    pub watched_files: Vec<String>,
+    pub total_disk_free_gb: Option<f64>,
+    pub config_dir_disk_space: Option<DiskSpaceResult>,
+    pub rust_version: String,
+    pub inotify: InotifyStats,
+}
+
+/// Space on the filesystem holding a path, in bytes
+struct DiskSpace {
+    /// Available to the current user, which may be less than the filesystem's free space
+    free: u64,
+    total: u64,
+    used: u64,
+}
+
+#[cfg(unix)]
+fn disk_space(path: &Path) -> Option<DiskSpace> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+
+    let block_size = stat.f_frsize as u64;
+    Some(DiskSpace {
+        free: stat.f_bavail as u64 * block_size,
+        total: stat.f_blocks as u64 * block_size,
+        used: (stat.f_blocks as u64).saturating_sub(stat.f_bfree as u64) * block_size,
+    })
+}
+
+#[cfg(windows)]
+fn disk_space(path: &Path) -> Option<DiskSpace> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let (mut free, mut total, mut total_free) = (0u64, 0u64, 0u64);
+    if unsafe { GetDiskFreeSpaceExW(wide.as_ptr(), &mut free, &mut total, &mut total_free) } == 0 {
+        return None;
+    }
+
+    Some(DiskSpace {
+        free,
+        total,
+        used: total.saturating_sub(total_free),
+    })
+}
+
+#[cfg(not(any(unix, windows)))]
+fn disk_space(_path: &Path) -> Option<DiskSpace> {
+    None
+}
+
+/// Free space available to the user on the filesystem holding path, in GB
+fn disk_free_gb(path: &Path) -> Option<f64> {
+    disk_space(path).map(|space| space.free as f64 / (1024.0 * 1024.0 * 1024.0))
+}
+
+/// Collect environment details for bug reports; never fails
+#[tauri::command]
+pub fn diagnostics(state: State<'_, AppState>) -> DiagnosticsReport {
+    let home_dir = dirs::home_dir();
+    let config_dir = get_config_dir();
+
+    let mut recent_file_count = 0;
+    let mut recent_file_parse_error = None;
+    if let Some(recent_path) = get_recent_file_path() {
+        if let Ok(content) = fs::read_to_string(&recent_path) {
+            match serde_json::from_str::<Vec<RecentFile>>(&content) {
+                Ok(files) => recent_file_count = files.len(),
+                Err(e) => recent_file_parse_error = Some(e.to_string()),
+            }
+        }
+    }
+
+    let mut watched_files: Vec<String> = match state.watchers.lock() {
+        Ok(map) => map.keys().cloned().collect(),
+        Err(_) => vec![],
+    };
+    watched_files.sort();
+
+    DiagnosticsReport {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        home_dir: home_dir.as_ref().map(|p| p.to_string_lossy().to_string()),
+        config_dir: config_dir.as_ref().map(|p| p.to_string_lossy().to_string()),
+        config_exists: config_dir.as_ref().is_some_and(|p| p.is_dir()),
+        recent_file_count,
+        recent_file_parse_error,
+        watched_files,
+        total_disk_free_gb: home_dir.as_deref().and_then(disk_free_gb),
+        config_dir_disk_space: config_dir.as_ref().map(|dir| get_disk_space(dir.to_string_lossy().into_owned())),
+        rust_version: env!("CARGO_PKG_RUST_VERSION").to_string(),
+        inotify: inotify_stats(&state),
+    }
+}
+
+/// Create a temp file in dest_path's directory, to be persisted over dest_path once
+/// fully written. Being next to the destination keeps the final rename on one filesystem
+fn temp_file_beside(dest_path: impl AsRef<Path>) -> std::io::Result<tempfile::NamedTempFile> {
+    let dest_dir = match dest_path.as_ref().parent() {
+        Some(d) if !d.as_os_str().is_empty() => d,
+        _ => Path::new("."),
+    };
+    tempfile::NamedTempFile::new_in(dest_dir)
+}
+
+// Lines between filtered-export-progress events
+const EXPORT_PROGRESS_INTERVAL: u64 = 50_000;
+
+/// Result for export_filtered_lines command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportFilteredResult {
+    pub success: bool,
+    pub lines_written: u64,
+    pub lines_scanned: u64,
+    pub bytes_written: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Payload for the "filtered-export-progress" event
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FilteredExportProgress {
+    pub path: String,
+    pub lines_scanned: u64,
+    pub lines_matched: u64,
+}
+
+/// Export only the lines matching pattern (plus surrounding context) to dest_path
+/// Overlapping context windows are written once; dest is replaced atomically
+#[tauri::command]
+pub fn export_filtered_lines(
+    app: AppHandle,
+    source_path: String,
+    dest_path: String,
+    pattern: String,
+    use_regex: bool,
+    case_sensitive: bool,
+    include_context: usize,
+) -> ExportFilteredResult {
+    let failed = |error: String| ExportFilteredResult {
+        success: false,
+        lines_written: 0,
+        lines_scanned: 0,
+        bytes_written: 0,
+        error: Some(error),
+    };
+
+    if source_path.is_empty() || dest_path.is_empty() || pattern.is_empty() {
+        return failed("Invalid parameters".to_string());
+    }
+
+    let matcher = match LineMatcher::new(&pattern, use_regex, case_sensitive) {
+        Ok(m) => m,
+        Err(e) => return failed(e),
+    };
+
+    let source = match File::open(&source_path) {
+        Ok(f) => f,
+        Err(_) => return failed("Cannot read file".to_string()),
+    };
+
+    let temp = match temp_file_beside(&dest_path) {
+        Ok(t) => t,
+        Err(e) => return failed(format!("Cannot write file: {}", e)),
+    };
+
+    let mut reader = BufReader::new(source);
+    let mut writer = BufWriter::new(temp);
+    let mut before: VecDeque<(u64, Vec<u8>)> = VecDeque::with_capacity(include_context);
+    let mut remaining_after = 0;
+    // First line index not yet written, so overlapping windows are not repeated
+    let mut next_unwritten: u64 = 0;
+    let mut lines_scanned: u64 = 0;
+    let mut lines_matched: u64 = 0;
+    let mut lines_written: u64 = 0;
+    let mut bytes_written: u64 = 0;
+    let mut buf = Vec::new();
+
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(_) => return failed("Cannot read file".to_string()),
+        }
+
+        let idx = lines_scanned;
+        lines_scanned += 1;
+
+        let is_match = matcher.is_match(&String::from_utf8_lossy(trim_line_ending(&buf)));
+        let mut to_write: Vec<Vec<u8>> = Vec::new();
+
+        if is_match {
+            lines_matched += 1;
+            to_write.extend(
+                before
+                    .drain(..)
+                    .filter(|(i, _)| *i >= next_unwritten)
+                    .map(|(_, line)| line),
+            );
+            to_write.push(buf.clone());
+            remaining_after = include_context;
+        } else if remaining_after > 0 {
+            to_write.push(buf.clone());
+            remaining_after -= 1;
+        } else if include_context > 0 {
+            if before.len() == include_context {
+                before.pop_front();
+            }
+            before.push_back((idx, buf.clone()));
+        }
+
+        if !to_write.is_empty() {
+            for line in &to_write {
+                if writer.write_all(line).is_err() {
+                    return failed("Cannot write file".to_string());
+                }
+                lines_written += 1;
+                bytes_written += line.len() as u64;
+            }
+            next_unwritten = idx + 1;
+        }
+
+        if lines_scanned % EXPORT_PROGRESS_INTERVAL == 0 {
+            let _ = app.emit("filtered-export-progress", FilteredExportProgress {
+                path: source_path.clone(),
+                lines_scanned,
+                lines_matched,
+            });
+        }
+    }
+
+    let temp = match writer.into_inner() {
+        Ok(t) => t,
+        Err(_) => return failed("Cannot write file".to_string()),
+    };
+    if let Err(e) = temp.persist(&dest_path) {
+        return failed(format!("Cannot write file: {}", e.error));
+    }
+
+    ExportFilteredResult {
+        success: true,
+        lines_written,
+        lines_scanned,
+        bytes_written,
+        error: None,
+    }
+}
+
+/// Give a recent file a human-readable alias shown in place of its filename
+/// An empty alias reverts to the filename
+#[tauri::command]
+pub fn rename_recent_file(state: State<'_, AppState>, path: String, alias: String) -> bool {
+    let alias = alias.trim();
+    with_recent_files(&state, |files| match files.iter_mut().find(|f| f.path == path) {
+        Some(entry) => {
+            entry.alias = if alias.is_empty() { None } else { Some(alias.to_string()) };
+            (true, true)
+        }
+        None => (false, false),
+    })
+    .unwrap_or(false)
+}
+
+/// Remove a recent file's alias so its filename is shown again
+#[tauri::command]
+pub fn clear_alias(state: State<'_, AppState>, path: String) -> bool {
+    rename_recent_file(state, path, String::new())
+}
+
+// Read buffer size for count_pattern_occurrences
+const COUNT_BUFFER_SIZE: usize = 256 * 1024;
+
+/// Result for count_pattern_occurrences command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CountResult {
+    pub success: bool,
+    pub count: u64,
+    pub total_lines: u64,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Count non-overlapping occurrences of needle in a file, plus the file's lines
+/// Scans COUNT_BUFFER_SIZE chunks, carrying the bytes after the last match (at most
+/// needle.len() - 1) into the next chunk so matches across a boundary are found
+fn count_literal(file: File, needle: &[u8]) -> std::io::Result<(u64, u64)> {
+    let finder = memchr::memmem::Finder::new(needle);
+    let mut reader = BufReader::with_capacity(COUNT_BUFFER_SIZE, file);
+    let mut window = Vec::with_capacity(COUNT_BUFFER_SIZE + needle.len());
+    let mut count = 0;
+    let mut newlines = 0;
+    let mut ends_with_newline = true;
+    loop {
+        let chunk = reader.fill_buf()?;
+        if chunk.is_empty() {
+            break;
+        }
+        newlines += memchr::memchr_iter(b'\n', chunk).count() as u64;
+        ends_with_newline = chunk[chunk.len() - 1] == b'\n';
+        window.extend_from_slice(chunk);
+        let consumed = chunk.len();
+        reader.consume(consumed);
+
+        let mut after_last_match = 0;
+        for at in finder.find_iter(&window) {
+            count += 1;
+            after_last_match = at + needle.len();
+        }
+        let carry_from = after_last_match.max(window.len().saturating_sub(needle.len() - 1));
+        window.drain(..carry_from);
+    }
+    Ok((count, newlines + u64::from(!ends_with_newline)))
+}
+
+/// Count (non-overlapping) occurrences of a pattern without loading the file
+#[tauri::command]
+pub fn count_pattern_occurrences(path: String, pattern: String, use_regex: bool, case_sensitive: bool) -> CountResult {
+    let failed = |error: String| CountResult {
+        success: false,
+        count: 0,
+        total_lines: 0,
+        duration_ms: 0,
+        error: Some(error),
+    };
+
+    if path.is_empty() {
+        return failed("No path provided".to_string());
+    }
+
+    if pattern.is_empty() {
+        return failed("Empty pattern".to_string());
+    }
+
+    // Regexes and case-insensitive text are matched line by line; exact text is
+    // searched for directly in the read chunks
+    let regex = if use_regex || !case_sensitive {
+        let source = match (use_regex, case_sensitive) {
+            (true, true) => pattern.clone(),
+            (true, false) => format!("(?i){}", pattern),
+            (false, _) => format!("(?i){}", regex::escape(&pattern)),
+        };
+        match regex::bytes::Regex::new(&source) {
+            Ok(re) => Some(re),
+            Err(e) => return failed(format!("Invalid regex: {}", e)),
+        }
+    } else {
+        None
+    };
+
+    let file = match File::open(&path) {
+        Ok(f) => f,
+        Err(_) => return failed("Cannot read file".to_string()),
+    };
+
+    let started = Instant::now();
+    let scanned = match regex {
+        Some(re) => {
+            let mut count = 0;
+            for_each_line(file, |line| count += re.find_iter(line).count() as u64).map(|lines| (count, lines))
+        }
+        None => count_literal(file, pattern.as_bytes()),
+    };
+    let (count, total_lines) = match scanned {
+        Ok(counted) => counted,
+        Err(_) => return failed("Cannot read file".to_string()),
+    };
+
+    CountResult {
+        success: true,
+        count,
+        total_lines,
+        duration_ms: started.elapsed().as_millis() as u64,
+        error: None,
+    }
+}
+
+/// Result for check_file_permissions command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionResult {
+    pub exists: bool,
+    pub readable: bool,
+    pub writable: bool,
+    pub is_symlink: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symlink_target: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unix_mode: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Check whether a file exists and can be read or written, each independently
+/// Explains a "Cannot open file" without the frontend having to guess why
+#[tauri::command]
+pub fn check_file_permissions(path: String) -> PermissionResult {
+    let mut result = PermissionResult {
+        exists: false,
+        readable: false,
+        writable: false,
+        is_symlink: false,
+        symlink_target: None,
+        unix_mode: None,
+        error: None,
+    };
+
+    if path.is_empty() {
+        result.error = Some("No path provided".to_string());
+        return result;
+    }
+
+    if let Ok(link_metadata) = fs::symlink_metadata(&path) {
+        result.is_symlink = link_metadata.file_type().is_symlink();
+        if result.is_symlink {
+            result.symlink_target = fs::read_link(&path)
+                .ok()
+                .map(|t| t.to_string_lossy().to_string());
+        }
+    }
+
+    // Follows symlinks, so a dangling link reports exists = false
+    match fs::metadata(&path) {
+        Ok(metadata) => {
+            result.exists = true;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::MetadataExt;
+                result.unix_mode = Some(metadata.mode());
+            }
+            #[cfg(not(unix))]
+            let _ = metadata;
+        }
+        Err(e) => {
+            result.error = Some(e.to_string());
+            return result;
+        }
+    }
+
+    match File::open(&path) {
+        Ok(_) => result.readable = true,
+        Err(e) => result.error = Some(format!("Cannot read: {}", e)),
+    }
+
+    // Opened without truncate or create, so the file is left untouched
+    match OpenOptions::new().write(true).open(&path) {
+        Ok(_) => result.writable = true,
+        Err(e) => {
+            if result.error.is_none() {
+                result.error = Some(format!("Cannot write: {}", e));
+            }
+        }
+    }
+
+    result
+}
+
+/// Result for get_file_permissions_string command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionsResult {
+    /// e.g. "0644"; Unix only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub octal: Option<String>,
+    /// e.g. "-rw-r--r--"; Unix only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbolic: Option<String>,
+    pub is_readonly: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// ls-style permission string for a Unix mode, including setuid/setgid/sticky bits
+#[cfg(unix)]
+fn symbolic_mode(mode: u32, is_dir: bool) -> String {
+    let mut symbolic = String::with_capacity(10);
+    symbolic.push(if is_dir { 'd' } else { '-' });
+
+    // (read, write, execute, special bit, special char) for user, group, other
+    let classes = [
+        (0o400, 0o200, 0o100, 0o4000, 's'),
+        (0o040, 0o020, 0o010, 0o2000, 's'),
+        (0o004, 0o002, 0o001, 0o1000, 't'),
+    ];
+    for (read, write, execute, special, special_char) in classes {
+        symbolic.push(if mode & read != 0 { 'r' } else { '-' });
+        symbolic.push(if mode & write != 0 { 'w' } else { '-' });
+        symbolic.push(match (mode & execute != 0, mode & special != 0) {
+            (true, true) => special_char,
+            (false, true) => special_char.to_ascii_uppercase(),
+            (true, false) => 'x',
+            (false, false) => '-',
+        });
+    }
+    symbolic
+}
+
+/// Human-readable permissions for a file info panel, e.g. "0644" and "-rw-r--r--"
+/// Only is_readonly is available on platforms without Unix modes
+#[tauri::command]
+pub fn get_file_permissions_string(path: String) -> PermissionsResult {
+    let metadata = match fs::metadata(&path) {
+        Ok(m) => m,
+        Err(e) => {
+            return PermissionsResult {
+                octal: None,
+                symbolic: None,
+                is_readonly: false,
+                error: Some(e.to_string()),
+            }
+        }
+    };
+
+    let permissions = metadata.permissions();
+
+    #[cfg(unix)]
+    let (octal, symbolic) = {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = permissions.mode();
+        (Some(format!("{:04o}", mode & 0o7777)), Some(symbolic_mode(mode, metadata.is_dir())))
+    };
+    #[cfg(not(unix))]
+    let (octal, symbolic) = (None, None);
+
+    PermissionsResult {
+        octal,
+        symbolic,
+        is_readonly: permissions.readonly(),
+        error: None,
+    }
+}
+
+// Format version written by export_recent_files
+const RECENT_EXPORT_VERSION: u32 = 1;
+
+/// Versioned envelope for an exported recent files list
+#[derive(Serialize, Deserialize)]
+struct RecentFilesEnvelope {
+    version: u32,
+    files: Vec<serde_json::Value>,
+}
+
+/// Result for import_recent_files command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportResult {
+    pub success: bool,
+    pub imported: usize,
+    pub skipped_duplicates: usize,
+    pub skipped_invalid: usize,
+    pub total_after: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Write the recent files list to dest_path for use on another machine
+/// Missing parent directories are created
+#[tauri::command]
+pub fn export_recent_files(dest_path: String) -> bool {
+    if dest_path.is_empty() {
+        return false;
+    }
+
+    let files = get_recent_file_path()
+        .and_then(|p| load_recent_files(&p))
+        .unwrap_or_default();
+
+    let files: Vec<serde_json::Value> = match files.iter().map(|f| serde_json::to_value(StoredRecentFile::from(f))).collect() {
+        Ok(f) => f,
+        Err(_) => return false,
+    };
+
+    let envelope = RecentFilesEnvelope {
+        version: RECENT_EXPORT_VERSION,
+        files,
+    };
+
+    if let Some(parent) = Path::new(&dest_path).parent().filter(|p| !p.as_os_str().is_empty()) {
+        if fs::create_dir_all(parent).is_err() {
+            return false;
+        }
+    }
+
+    match serde_json::to_string_pretty(&envelope) {
+        Ok(json) => write_file_atomically(Path::new(&dest_path), json.as_bytes()),
+        Err(_) => false,
+    }
+}
+
+/// Import a recent files list written by export_recent_files
+/// With merge set, entries not already present are prepended to the current list;
+/// otherwise the current list is replaced. Entries that don't parse are skipped
+#[tauri::command]
+pub fn import_recent_files(state: State<'_, AppState>, source_path: String, merge: bool) -> ImportResult {
+    let failed = |error: &str| ImportResult {
+        success: false,
+        imported: 0,
+        skipped_duplicates: 0,
+        skipped_invalid: 0,
+        total_after: 0,
+        error: Some(error.to_string()),
+    };
+
+    let content = match fs::read_to_string(&source_path) {
+        Ok(c) => c,
+        Err(_) => return failed("Cannot read file"),
+    };
+
+    // A bare array is accepted too, e.g. a copied recent.json
+    let entries = match serde_json::from_str::<RecentFilesEnvelope>(&content) {
+        Ok(envelope) if envelope.version > RECENT_EXPORT_VERSION => {
+            return failed("Unsupported export version");
+        }
+        Ok(envelope) => envelope.files,
+        Err(_) => match serde_json::from_str::<Vec<serde_json::Value>>(&content) {
+            Ok(files) => files,
+            Err(_) => return failed("Not a recent files export"),
+        },
+    };
+
+    let imported = with_recent_files(&state, |current| {
+        if !merge {
+            current.clear();
+        }
+
+        let mut incoming: Vec<RecentFile> = Vec::new();
+        let mut skipped_duplicates = 0;
+        let mut skipped_invalid = 0;
+        for entry in entries {
+            let mut file = match serde_json::from_value::<RecentFile>(entry) {
+                Ok(f) if !f.path.is_empty() => f,
+                _ => {
+                    skipped_invalid += 1;
+                    continue;
+                }
+            };
+
+            if current.iter().chain(incoming.iter()).any(|f| f.path == file.path) {
+                skipped_duplicates += 1;
+                continue;
+            }
+
+            // Exported names carry the alias; the filename comes from the path
+            file.name = get_filename(&file.path);
+            incoming.push(file);
+        }
+
+        let imported = incoming.len().min(MAX_RECENT);
+        current.splice(0..0, incoming);
+        current.truncate(MAX_RECENT);
+        ((imported, skipped_duplicates, skipped_invalid, current.len()), true)
+    });
+
+    let (imported, skipped_duplicates, skipped_invalid, total_after) = match imported {
+        Some(counts) => counts,
+        None => return failed("Cannot write recent files"),
+    };
+
+    ImportResult {
+        success: true,
+        imported,
+        skipped_duplicates,
+        skipped_invalid,
+        total_after,
+        error: None,
+    }
+}
+
+/// Run f against the line index for path, building (and caching) it if needed
+/// A cached index is reused only while the file's size and mtime are unchanged
+fn with_line_index<T>(
+    state: &AppState,
+    path: &str,
+    metadata: &fs::Metadata,
+    f: impl FnOnce(&LineIndex) -> std::io::Result<T>,
+) -> Result<T, &'static str> {
+    let size = metadata.len();
+    let mtime = mtime_millis(metadata);
+
+    if let Ok(mut cache) = state.line_index_cache.lock() {
+        if let Some(index) = cache.get(path).filter(|i| i.size == size && i.mtime == mtime) {
+            return f(index).map_err(|_| "Cannot read file");
+        }
+    }
+
+    // Build without holding the lock so other reads aren't stalled by the scan
+    let index = LineIndex::build(Path::new(path), mtime).map_err(|_| "Cannot read file")?;
+    let result = f(&index).map_err(|_| "Cannot read file")?;
+    if let Ok(mut cache) = state.line_index_cache.lock() {
+        cache.put(path.to_string(), index);
+    }
+    Ok(result)
+}
+
+/// 1-indexed line number of the line starting at offset, from the cached line index
+/// None if no up-to-date index is cached; unlike with_line_index this never scans the
+/// whole file, only from the nearest indexed line
+fn cached_line_number(state: &AppState, path: &str, metadata: &fs::Metadata, offset: u64) -> Option<usize> {
+    let mtime = mtime_millis(metadata);
+    let mut cache = state.line_index_cache.lock().ok()?;
+    let index = cache.get(path).filter(|i| i.size == metadata.len() && i.mtime == mtime)?;
+    index.line_at_offset(Path::new(path), offset).ok().map(|line| line + 1)
+}
+
+/// Read the lines around line_number (1-indexed), e.g. to jump to "app.log:4823"
+/// Line offsets are cached, so repeat jumps into an unchanged file skip the scan
+#[tauri::command]
+pub fn read_file_at_line(
+    state: State<'_, AppState>,
+    path: String,
+    line_number: usize,
+    lines_before: usize,
+    lines_after: usize,
+) -> FileResult {
+    if path.is_empty() || line_number == 0 {
+        return FileResult::error("Invalid parameters");
+    }
+
+    let metadata = match fs::metadata(&path) {
+        Ok(m) => m,
+        Err(_) => return FileResult::error("Cannot open file"),
+    };
+
+    let first_wanted = line_number.saturating_sub(lines_before).max(1);
+    let last_wanted = line_number.saturating_add(lines_after);
+
+    let lookup = with_line_index(&state, &path, &metadata, |index| {
+        Ok((index.line_count(), index.byte_range(Path::new(&path), first_wanted - 1, last_wanted - 1)?))
+    });
+    let (total_lines, range) = match lookup {
+        Ok(l) => l,
+        Err(e) => return FileResult::error(e),
+    };
+
+    let (start, end) = match range {
+        Some(r) if line_number <= total_lines => r,
+        _ => {
+            return FileResult {
+                total_lines: Some(total_lines),
+                ..FileResult::error("Line number out of range")
+            };
+        }
+    };
+
+    let content = match read_range_lossy(&path, start, end - start) {
+        Ok(c) => c,
+        Err(e) => return FileResult::error(e),
+    };
+    let replacement_char_count = count_replacements(&content);
+    let lines: Vec<&str> = content.lines().collect();
+
+    FileResult {
+        success: true,
+        content: Some(lines.join("\n")),
+        path: Some(path.clone()),
+        name: Some(get_filename(&path)),
+        display_name: get_filename(&path),
+        size: Some(metadata.len()),
+        read_from_offset: start,
+        next_offset: end,
+        mtime: mtime_millis(&metadata),
+        mtime_iso: mtime_millis(&metadata).and_then(mtime_iso),
+        lines_returned: Some(lines.len()),
+        start_line: Some(first_wanted),
+        end_line: Some(first_wanted + lines.len() - 1),
+        total_lines: Some(total_lines),
+        replacement_char_count: Some(replacement_char_count),
+        error: encoding_warning(replacement_char_count),
+        ..Default::default()
+    }
+}
+
+/// Read lines start_line..=end_line (1-indexed), clamped to the end of the file
+/// Shares read_file_at_line's cached line index
+#[tauri::command]
+pub fn read_file_range(state: State<'_, AppState>, path: String, start_line: usize, end_line: usize) -> FileResult {
+    if end_line < start_line {
+        return FileResult::error("Invalid parameters");
+    }
+    read_file_at_line(state, path, start_line, 0, end_line - start_line)
+}
+
+/// Get the active configuration
+#[tauri::command]
+pub fn get_config(state: State<'_, AppState>) -> Config {
+    match state.config.read() {
+        Ok(config) => config.clone(),
+        Err(_) => Config::default(),
+    }
+}
+
+// Apache Common Log Format, e.g.
+// 127.0.0.1 - frank [10/Oct/2000:13:55:36 -0700] "GET /index.html HTTP/1.1" 200 2326
+const APACHE_CLF_PATTERN: &str = r#"^(?P<ip>\S+) \S+ (?P<user>\S+) \[(?P<timestamp>[^\]]+)\] "(?P<method>\S+) (?P<path>\S+)(?: (?P<protocol>[^"]*))?" (?P<status>\d{3}) (?P<bytes>\d+|-)"#;
+
+// Number of most requested paths returned by parse_apache_access_log
+const TOP_PATHS_LIMIT: usize = 10;
+
+/// A single parsed access log request
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApacheLogEntry {
+    pub line_number: usize,
+    pub ip: String,
+    pub user: String,
+    pub timestamp: String,
+    pub method: String,
+    pub path: String,
+    pub protocol: String,
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<u64>,
+}
+
+/// Result for parse_apache_access_log command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApacheLogResult {
+    pub success: bool,
+    pub entries: Vec<ApacheLogEntry>,
+    pub total_lines: usize,
+    pub failed_lines: usize,
+    pub truncated: bool,
+    /// Request counts by status class ("2xx", "4xx", ...)
+    pub status_distribution: HashMap<String, u64>,
+    /// Most requested paths with their counts, most frequent first
+    pub top_paths: Vec<(String, u64)>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Parse one access log line with a CLF-style regex; None if it doesn't match
+fn parse_access_log_line(re: &Regex, line: &str, line_number: usize) -> Option<ApacheLogEntry> {
+    access_log_entry(&re.captures(line)?, line_number)
+}
+
+/// Build an access log entry from the named groups of a CLF-style regex match
+fn access_log_entry(caps: &regex::Captures, line_number: usize) -> Option<ApacheLogEntry> {
+    let field = |name: &str| caps.name(name).map_or("", |m| m.as_str()).to_string();
+
+    Some(ApacheLogEntry {
+        line_number,
+        ip: field("ip"),
+        user: field("user"),
+        timestamp: field("timestamp"),
+        method: field("method"),
+        path: field("path"),
+        protocol: field("protocol"),
+        status: field("status").parse().ok()?,
+        bytes: field("bytes").parse().ok(),
+    })
+}
+
+/// The most frequent keys with their counts, ties broken alphabetically
+fn top_counts(counts: HashMap<String, u64>, limit: usize) -> Vec<(String, u64)> {
+    let mut sorted: Vec<(String, u64)> = counts.into_iter().collect();
+    sorted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    sorted.truncate(limit);
+    sorted
+}
+
+/// Parse an Apache Common Log Format access log into structured requests
+/// Status and path statistics cover every parsed line, even past the entry cap
+#[tauri::command]
+pub fn parse_apache_access_log(path: String, max_lines: Option<usize>) -> ApacheLogResult {
+    let failed = |error: &str| ApacheLogResult {
+        success: false,
+        entries: vec![],
+        total_lines: 0,
+        failed_lines: 0,
+        truncated: false,
+        status_distribution: HashMap::new(),
+        top_paths: vec![],
+        error: Some(error.to_string()),
+    };
+
+    let re = match Regex::new(APACHE_CLF_PATTERN) {
+        Ok(re) => re,
+        Err(_) => return failed("Invalid log pattern"),
+    };
+
+    let file = match File::open(&path) {
+        Ok(f) => f,
+        Err(_) => return failed("Cannot open file"),
+    };
+
+    let mut reader = BufReader::new(file);
+    let mut entries = Vec::new();
+    let mut status_distribution: HashMap<String, u64> = HashMap::new();
+    let mut path_counts: HashMap<String, u64> = HashMap::new();
+    let mut total_lines = 0;
+    let mut failed_lines = 0;
+    let mut truncated = false;
+    let mut buf = Vec::new();
+
+    while max_lines.map_or(true, |max| total_lines < max) {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(_) => return failed("Cannot read file"),
+        }
+
+        total_lines += 1;
+        let line = String::from_utf8_lossy(trim_line_ending(&buf));
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let entry = match parse_access_log_line(&re, &line, total_lines) {
+            Some(e) => e,
+            None => {
+                failed_lines += 1;
+                continue;
+            }
+        };
+
+        *status_distribution.entry(format!("{}xx", entry.status / 100)).or_insert(0) += 1;
+        *path_counts.entry(entry.path.clone()).or_insert(0) += 1;
+
+        if entries.len() < MAX_PARSED_ENTRIES {
+            entries.push(entry);
+        } else {
+            truncated = true;
+        }
+    }
+
+    ApacheLogResult {
+        success: true,
+        entries,
+        total_lines,
+        failed_lines,
+        truncated,
+        status_distribution,
+        top_paths: top_counts(path_counts, TOP_PATHS_LIMIT),
+        error: None,
+    }
+}
+
+/// Result for filter_by_time_range command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilteredResult {
+    pub success: bool,
+    pub content: String,
+    pub matched_lines: usize,
+    pub total_lines: usize,
+    pub truncated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Extract the lines whose timestamps fall within [from_ts, to_ts] (Unix millis)
+/// Lines without a parseable timestamp (e.g. stack traces) take the timestamp of
+/// the line before them. context_lines surrounding lines are included as well
+#[tauri::command]
+pub fn filter_by_time_range(
+    path: String,
+    timestamp_pattern: String,
+    from_ts: i64,
+    to_ts: i64,
+    context_lines: usize,
+) -> FilteredResult {
+    let failed = |error: &str| FilteredResult {
+        success: false,
+        content: String::new(),
+        matched_lines: 0,
+        total_lines: 0,
+        truncated: false,
+        error: Some(error.to_string()),
+    };
+
+    if path.is_empty() || timestamp_pattern.is_empty() || from_ts > to_ts {
+        return failed("Invalid parameters");
+    }
+
+    let file = match File::open(&path) {
+        Ok(f) => f,
+        Err(_) => return failed("Cannot open file"),
+    };
+
+    let mut reader = BufReader::new(file);
+    let mut before: VecDeque<(usize, String)> = VecDeque::with_capacity(context_lines);
+    let mut output: Vec<String> = Vec::new();
+    let mut output_bytes: u64 = 0;
+    let mut remaining_after = 0;
+    // First line index not yet output, so overlapping windows are not repeated
+    let mut next_unwritten = 0;
+    let mut current_ts: Option<i64> = None;
+    let mut matched_lines = 0;
+    let mut total_lines = 0;
+    let mut truncated = false;
+    let mut buf = Vec::new();
+
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(_) => return failed("Cannot read file"),
+        }
+
+        let idx = total_lines;
+        total_lines += 1;
+        let line = String::from_utf8_lossy(trim_line_ending(&buf)).into_owned();
+
+        if let Some(ts) = parse_line_timestamp(&line, &timestamp_pattern) {
+            current_ts = Some(ts);
+        }
+        let in_range = current_ts.is_some_and(|ts| ts >= from_ts && ts <= to_ts);
+
+        let mut to_output: Vec<String> = Vec::new();
+        if in_range {
+            matched_lines += 1;
+            to_output.extend(
+                before
+                    .drain(..)
+                    .filter(|(i, _)| *i >= next_unwritten)
+                    .map(|(_, l)| l),
+            );
+            to_output.push(line);
+            remaining_after = context_lines;
+        } else if remaining_after > 0 {
+            to_output.push(line);
+            remaining_after -= 1;
+        } else if context_lines > 0 {
+            if before.len() == context_lines {
+                before.pop_front();
+            }
+            before.push_back((idx, line));
+        }
+
+        if !to_output.is_empty() {
+            next_unwritten = idx + 1;
+            for l in to_output {
+                // Keep counting matches, but stop growing the IPC payload
+                if truncated || output_bytes + l.len() as u64 > MAX_READ_SIZE {
+                    truncated = true;
+                    break;
+                }
+                output_bytes += l.len() as u64 + 1;
+                output.push(l);
+            }
+        }
+    }
+
+    FilteredResult {
+        success: true,
+        content: output.join("\n"),
+        matched_lines,
+        total_lines,
+        truncated,
+        error: None,
+    }
+}
+
+// Distinct messages tracked by get_top_messages before rare ones are pruned
+const MAX_TRACKED_MESSAGES: usize = 1_000_000;
+
+/// How often a message occurs and where
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageFrequency {
+    pub message: String,
+    pub count: u64,
+    pub first_seen_line: usize,
+    pub last_seen_line: usize,
+}
+
+/// Result for get_top_messages command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopMessagesResult {
+    pub success: bool,
+    pub entries: Vec<MessageFrequency>,
+    /// Distinct messages seen. Exact unless approximate is set; then a pruned message
+    /// that turns up again is counted a second time, so this is an upper bound
+    pub total_unique: u64,
+    pub total_lines: u64,
+    pub duration_ms: u64,
+    /// True when rare messages were pruned to bound memory, making counts approximate
+    pub approximate: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Replace every run of digits with a single "#"
+fn normalize_digits(line: &str) -> String {
+    let mut normalized = String::with_capacity(line.len());
+    let mut in_digits = false;
+    for c in line.chars() {
+        if c.is_ascii_digit() {
+            if !in_digits {
+                normalized.push('#');
+            }
+            in_digits = true;
+        } else {
+            normalized.push(c);
+            in_digits = false;
+        }
+    }
+    normalized
+}
+
+/// Find the top_n most repeated lines in a file
+/// With normalize_numbers set, lines differing only in their numbers share a bucket.
+/// Past MAX_TRACKED_MESSAGES distinct lines, the rarest are dropped (approximate)
+#[tauri::command]
+pub fn get_top_messages(path: String, top_n: usize, normalize_numbers: bool) -> TopMessagesResult {
+    let failed = |error: &str| TopMessagesResult {
+        success: false,
+        entries: vec![],
+        total_unique: 0,
+        total_lines: 0,
+        duration_ms: 0,
+        approximate: false,
+        error: Some(error.to_string()),
+    };
+
+    let file = match File::open(&path) {
+        Ok(f) => f,
+        Err(_) => return failed("Cannot open file"),
+    };
+
+    let started = Instant::now();
+    let mut reader = BufReader::new(file);
+    // message -> (count, first_seen_line, last_seen_line)
+    let mut counts: HashMap<String, (u64, usize, usize)> = HashMap::new();
+    let mut total_unique: u64 = 0;
+    let mut total_lines: u64 = 0;
+    let mut prune_below: u64 = 1;
+    let mut approximate = false;
+    let mut buf = Vec::new();
+
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(_) => return failed("Cannot read file"),
+        }
+
+        total_lines += 1;
+        let line_number = total_lines as usize;
+        let line = String::from_utf8_lossy(trim_line_ending(&buf));
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let message = if normalize_numbers {
+            normalize_digits(&line)
+        } else {
+            line.into_owned()
+        };
+
+        match counts.get_mut(&message) {
+            Some(entry) => {
+                entry.0 += 1;
+                entry.2 = line_number;
+            }
+            None => {
+                // Can't tell a pruned message from a new one, hence the upper bound
+                total_unique += 1;
+                counts.insert(message, (1, line_number, line_number));
+            }
+        }
+
+        // Drop the rarest messages, raising the bar until the table fits again
+        while counts.len() > MAX_TRACKED_MESSAGES {
+            counts.retain(|_, (count, _, _)| *count > prune_below);
+            prune_below += 1;
+            approximate = true;
+        }
+    }
+
+    // Min-heap of the best top_n so far, keyed by count then earliest first sighting
+    let mut heap: BinaryHeap<Reverse<(u64, Reverse<usize>, String)>> = BinaryHeap::new();
+    for (message, (count, first_seen, _)) in &counts {
+        heap.push(Reverse((*count, Reverse(*first_seen), message.clone())));
+        if heap.len() > top_n {
+            heap.pop();
+        }
+    }
+
+    let entries = heap.into_sorted_vec()
+        .into_iter()
+        .map(|Reverse((count, Reverse(first_seen_line), message))| {
+            let last_seen_line = counts.get(&message).map_or(first_seen_line, |c| c.2);
+            MessageFrequency {
+                message,
+                count,
+                first_seen_line,
+                last_seen_line,
+            }
+        })
+        .collect();
+
+    TopMessagesResult {
+        success: true,
+        entries,
+        total_unique,
+        total_lines,
+        duration_ms: started.elapsed().as_millis() as u64,
+        approximate,
+        error: None,
+    }
+}
+
+// Largest file diff_files will load
+const MAX_DIFF_FILE_SIZE: u64 = 50 * 1024 * 1024;
+
+// Context lines around each hunk in unified output
+const UNIFIED_CONTEXT_LINES: usize = 3;
+
+/// A line that exists on only one side of a diff
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffLine {
+    pub line_number: usize,
+    pub content: String,
+}
+
+/// A run of differing lines; ranges are 1-indexed and end-exclusive
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffBlock {
+    pub kind: String,
+    pub a_start: usize,
+    pub a_end: usize,
+    pub b_start: usize,
+    pub b_end: usize,
+}
+
+/// Result for diff_files command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffResult {
+    pub success: bool,
+    pub only_in_a: Vec<DiffLine>,
+    pub only_in_b: Vec<DiffLine>,
+    pub changed_blocks: Vec<DiffBlock>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unified_diff: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// FileTooLarge when either file is over MAX_DIFF_FILE_SIZE
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<CommandError>,
+}
+
+/// Read a whole file for diffing, refusing anything over MAX_DIFF_FILE_SIZE
+/// Errors carry a CommandError for failures the frontend handles specially
+fn read_for_diff(path: &str) -> Result<String, (String, Option<CommandError>)> {
+    let metadata = fs::metadata(path).map_err(|_| (format!("Cannot open file: {}", path), None))?;
+    if metadata.len() > MAX_DIFF_FILE_SIZE {
+        return Err((format!("File too large to diff: {}", path), Some(CommandError::FileTooLarge)));
+    }
+    let bytes = fs::read(path).map_err(|_| (format!("Cannot read file: {}", path), None))?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Diff two files line by line
+/// algorithm "lines-added" reports the lines in B that aren't in A;
+/// "unified" reports both sides and also renders a unified diff
+#[tauri::command]
+pub fn diff_files(path_a: String, path_b: String, algorithm: String) -> DiffResult {
+    let failed_with_code = |error: String, error_code: Option<CommandError>| DiffResult {
+        success: false,
+        only_in_a: vec![],
+        only_in_b: vec![],
+        changed_blocks: vec![],
+        unified_diff: None,
+        error: Some(error),
+        error_code,
+    };
+    let failed = |error: String| failed_with_code(error, None);
+
+    let unified = match algorithm.as_str() {
+        "lines-added" => false,
+        "unified" => true,
+        _ => return failed(format!("Unknown diff algorithm: {}", algorithm)),
+    };
+
+    let text_a = match read_for_diff(&path_a) {
+        Ok(t) => t,
+        Err((e, code)) => return failed_with_code(e, code),
+    };
+    let text_b = match read_for_diff(&path_b) {
+        Ok(t) => t,
+        Err((e, code)) => return failed_with_code(e, code),
+    };
+
+    let lines_a: Vec<&str> = text_a.lines().collect();
+    let lines_b: Vec<&str> = text_b.lines().collect();
+    let diff = similar::TextDiff::from_lines(&text_a, &text_b);
+
+    let to_diff_lines = |lines: &[&str], range: std::ops::Range<usize>| -> Vec<DiffLine> {
+        range
+            .filter_map(|i| {
+                lines.get(i).map(|l| DiffLine {
+                    line_number: i + 1,
+                    content: l.to_string(),
+                })
+            })
+            .collect()
+    };
+
+    let mut only_in_a = Vec::new();
+    let mut only_in_b = Vec::new();
+    let mut changed_blocks = Vec::new();
+
+    for op in diff.ops() {
+        let (tag, range_a, range_b) = op.as_tag_tuple();
+        let kind = match tag {
+            similar::DiffTag::Equal => continue,
+            similar::DiffTag::Delete => "delete",
+            similar::DiffTag::Insert => "insert",
+            similar::DiffTag::Replace => "replace",
+        };
+
+        if unified {
+            only_in_a.extend(to_diff_lines(&lines_a, range_a.clone()));
+        }
+        only_in_b.extend(to_diff_lines(&lines_b, range_b.clone()));
+
+        changed_blocks.push(DiffBlock {
+            kind: kind.to_string(),
+            a_start: range_a.start + 1,
+            a_end: range_a.end + 1,
+            b_start: range_b.start + 1,
+            b_end: range_b.end + 1,
+        });
+    }
+
+    let unified_diff = if unified {
+        Some(
+            diff.unified_diff()
+                .context_radius(UNIFIED_CONTEXT_LINES)
+                .header(&path_a, &path_b)
+                .to_string(),
+        )
+    } else {
+        None
+    };
+
+    DiffResult {
+        success: true,
+        only_in_a,
+        only_in_b,
+        changed_blocks,
+        unified_diff,
+        error: None,
+        error_code: None,
+    }
+}
+
+/// Result for validate_recent_json command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationResult {
+    pub valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry_count: Option<usize>,
+    pub recovered: bool,
+}
+
+/// Restore recent.json from recent.json.bak if the backup parses, returning its entry count
+/// Callers hold recent_files_lock so the restore can't interleave with a list update
+fn restore_recent_backup(recent_path: &Path) -> Option<usize> {
+    let backup_path = get_recent_backup_path()?;
+    let contents = fs::read(&backup_path).ok()?;
+    let files: Vec<RecentFile> = serde_json::from_slice(&contents).ok()?;
+    if !write_file_atomically(recent_path, &contents) {
+        return None;
+    }
+    Some(files.len())
+}
+
+/// Check that recent.json parses, restoring it from recent.json.bak if it doesn't
+/// A missing recent.json is valid (no files opened yet)
+#[tauri::command]
+pub fn validate_recent_json(state: State<'_, AppState>) -> ValidationResult {
+    let _guard = match state.recent_files_lock.lock() {
+        Ok(g) => g,
+        Err(_) => {
+            return ValidationResult {
+                valid: false,
+                error: Some("Recent files list is unavailable".to_string()),
+                entry_count: None,
+                recovered: false,
+            }
+        }
+    };
+
+    let recent_path = match get_recent_file_path() {
+        Some(p) => p,
+        None => {
+            return ValidationResult {
+                valid: false,
+                error: Some("Could not determine home directory".to_string()),
+                entry_count: None,
+                recovered: false,
+            }
+        }
+    };
+
+    if !recent_path.exists() {
+        return ValidationResult {
+            valid: true,
+            error: None,
+            entry_count: Some(0),
+            recovered: false,
+        };
+    }
+
+    let parsed = fs::read_to_string(&recent_path)
+        .map_err(|e| e.to_string())
+        .and_then(|c| serde_json::from_str::<Vec<RecentFile>>(&c).map_err(|e| e.to_string()));
+
+    let error = match parsed {
+        Ok(files) => {
+            return ValidationResult {
+                valid: true,
+                error: None,
+                entry_count: Some(files.len()),
+                recovered: false,
+            }
+        }
+        Err(e) => e,
+    };
+
+    let entry_count = restore_recent_backup(&recent_path);
+    ValidationResult {
+        valid: false,
+        error: Some(error),
+        entry_count,
+        recovered: entry_count.is_some(),
+    }
+}
+
+/// Restore recent.json from its backup if it is corrupted
+/// Returns true if recent.json is usable afterwards
+#[tauri::command]
+pub fn repair_recent_json(state: State<'_, AppState>) -> bool {
+    let result = validate_recent_json(state);
+    result.valid || result.recovered
+}
+
+// Bytes read from the head, middle and tail of a file to estimate its line count
+const SAMPLE_WINDOW_SIZE: u64 = 64 * 1024;
+
+// Longest line returned by read_file_sample; longer lines are cut off
+const MAX_SAMPLE_LINE_BYTES: u64 = 64 * 1024;
+
+/// A single line picked by read_file_sample
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SampledLine {
+    /// 1-indexed; estimated from the average line length for large files
+    pub line_number: u64,
+    pub byte_offset: u64,
+    pub content: String,
+}
+
+/// Result for read_file_sample command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SampleResult {
+    pub success: bool,
+    pub lines: Vec<SampledLine>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_lines: Option<u64>,
+    /// Approximate number of lines between consecutive samples
+    pub sample_interval: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// (start, len) of equal windows at the start, middle and end of a file
+/// Only meaningful when size is at least three windows long
+fn head_middle_tail_windows(size: u64, window: u64) -> [(u64, u64); 3] {
+    [(0, window), (size / 2 - window / 2, window), (size - window, window)]
+}
+
+/// Estimate a file's line count and average line length from a few windows of it
+/// Small files are counted exactly. Returns None if no newline was seen
+fn estimate_line_stats(file: &mut File, size: u64) -> std::io::Result<Option<(u64, f64)>> {
+    let windows = if size <= SAMPLE_WINDOW_SIZE * 3 {
+        vec![(0, size)]
+    } else {
+        head_middle_tail_windows(size, SAMPLE_WINDOW_SIZE).to_vec()
+    };
+
+    let mut sampled_bytes: u64 = 0;
+    let mut newlines: u64 = 0;
+    let mut buf = Vec::new();
+    for (start, len) in windows {
+        buf.clear();
+        file.seek(SeekFrom::Start(start))?;
+        (&mut *file).take(len).read_to_end(&mut buf)?;
+        sampled_bytes += buf.len() as u64;
+        newlines += memchr::memchr_iter(b'\n', &buf).count() as u64;
+    }
+
+    if newlines == 0 {
+        return Ok(None);
+    }
+
+    let avg_line_len = sampled_bytes as f64 / newlines as f64;
+    let total_lines = if size <= SAMPLE_WINDOW_SIZE * 3 {
+        // A final line without a trailing newline still counts
+        newlines + u64::from(!buf.ends_with(b"\n"))
+    } else {
+        (size as f64 / avg_line_len).round() as u64
+    };
+    Ok(Some((total_lines, avg_line_len)))
+}
+
+/// Return sample_count lines spread evenly across a file, to get a feel for its format
+/// Only a few windows of the file are read, so this is cheap even for huge logs
+#[tauri::command]
+pub fn read_file_sample(path: String, sample_count: usize) -> SampleResult {
+    let failed = |error: &str| SampleResult {
+        success: false,
+        lines: vec![],
+        total_lines: None,
+        sample_interval: 0,
+        error: Some(error.to_string()),
+    };
+
+    if path.is_empty() {
+        return failed("No path provided");
+    }
+
+    let mut file = match File::open(&path) {
+        Ok(f) => f,
+        Err(_) => return failed("Cannot open file"),
+    };
+    let size = match file.metadata() {
+        Ok(m) => m.len(),
+        Err(_) => return failed("Cannot read file metadata"),
+    };
+
+    let stats = match estimate_line_stats(&mut file, size) {
+        Ok(s) => s,
+        Err(_) => return failed("Cannot read file"),
+    };
+    let total_lines = stats.map(|(total, _)| total);
+    let sample_interval = match total_lines {
+        Some(total) if sample_count > 0 => (total / sample_count as u64).max(1),
+        _ => 0,
+    };
+
+    let mut reader = BufReader::new(file);
+    let mut lines: Vec<SampledLine> = Vec::with_capacity(sample_count);
+    let mut buf = Vec::new();
+
+    for i in 0..sample_count as u64 {
+        let target = (i as u128 * size as u128 / sample_count as u128) as u64;
+
+        // Back up one byte and skip to the next newline, so a target that is already
+        // at a line start picks that line rather than the following one
+        let line_start = if target == 0 {
+            0
+        } else {
+            if reader.seek(SeekFrom::Start(target - 1)).is_err() {
+                return failed("Cannot seek in file");
+            }
+            buf.clear();
+            match reader.read_until(b'\n', &mut buf) {
+                Ok(n) => target - 1 + n as u64,
+                Err(_) => return failed("Cannot read file"),
+            }
+        };
+
+        if line_start >= size {
+            break;
+        }
+        // Short lines can make neighbouring targets land on the same line
+        if lines.last().is_some_and(|l| l.byte_offset >= line_start) {
+            continue;
+        }
+
+        if reader.seek(SeekFrom::Start(line_start)).is_err() {
+            return failed("Cannot seek in file");
+        }
+        buf.clear();
+        if reader.by_ref().take(MAX_SAMPLE_LINE_BYTES).read_until(b'\n', &mut buf).is_err() {
+            return failed("Cannot read file");
+        }
+
+        let line_number = match stats {
+            Some((_, avg_line_len)) => (line_start as f64 / avg_line_len).round() as u64 + 1,
+            None => 1,
+        };
+        lines.push(SampledLine {
+            line_number,
+            byte_offset: line_start,
+            content: String::from_utf8_lossy(trim_line_ending(&buf)).into_owned(),
+        });
+    }
+
+    SampleResult {
+        success: true,
+        lines,
+        total_lines,
+        sample_interval,
+        error: None,
+    }
+}
+
+// Estimated memory for the exact seen-line set before falling back to a Bloom filter
+const UNIQUE_SET_MEMORY_LIMIT: u64 = 512 * 1024 * 1024;
+
+// Rough per-line bookkeeping cost of a HashSet<Vec<u8>> entry on top of the line itself
+const UNIQUE_SET_ENTRY_OVERHEAD: u64 = 48;
+
+// False positive rate of the fallback Bloom filter
+const BLOOM_FALSE_POSITIVE_RATE: f64 = 0.001;
+
+/// Fixed-size Bloom filter: can report a new item as seen, never the other way round
+struct BloomFilter {
+    bits: Vec<u64>,
+    num_bits: u64,
+    num_hashes: u32,
+}
+
+impl BloomFilter {
+    fn new(expected_items: u64, false_positive_rate: f64) -> Self {
+        let n = expected_items.max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let num_bits = ((-n * false_positive_rate.ln() / (ln2 * ln2)).ceil() as u64).max(64);
+        let num_hashes = ((num_bits as f64 / n) * ln2).round().max(1.0) as u32;
+        BloomFilter {
+            bits: vec![0; num_bits.div_ceil(64) as usize],
+            num_bits,
+            num_hashes,
+        }
+    }
+
+    /// Add an item, returning false if it was (probably) already present
+    fn insert(&mut self, item: &[u8]) -> bool {
+        let mut hasher = DefaultHasher::new();
+        item.hash(&mut hasher);
+        let h1 = hasher.finish();
+        h1.hash(&mut hasher);
+        let h2 = hasher.finish() | 1;
+
+        let mut is_new = false;
+        for i in 0..self.num_hashes as u64 {
+            let bit = h1.wrapping_add(i.wrapping_mul(h2)) % self.num_bits;
+            let (word, mask) = ((bit / 64) as usize, 1u64 << (bit % 64));
+            if self.bits[word] & mask == 0 {
+                self.bits[word] |= mask;
+                is_new = true;
+            }
+        }
+        is_new
+    }
+}
+
+/// Lines seen so far: exact until UNIQUE_SET_MEMORY_LIMIT, then a Bloom filter
+enum SeenLines {
+    Exact { set: HashSet<Vec<u8>>, bytes: u64 },
+    Approximate(BloomFilter),
+}
+
+impl SeenLines {
+    /// Record a line, returning true the first time it is seen
+    /// estimated_lines sizes the Bloom filter if this insert triggers the fallback
+    fn insert(&mut self, line: &[u8], estimated_lines: u64) -> bool {
+        match self {
+            SeenLines::Exact { set, bytes } => {
+                if set.contains(line) {
+                    return false;
+                }
+                *bytes += line.len() as u64 + UNIQUE_SET_ENTRY_OVERHEAD;
+                set.insert(line.to_vec());
+                if *bytes > UNIQUE_SET_MEMORY_LIMIT {
+                    let mut bloom = BloomFilter::new(estimated_lines.max(set.len() as u64 * 2), BLOOM_FALSE_POSITIVE_RATE);
+                    for seen in set.iter() {
+                        bloom.insert(seen);
+                    }
+                    *self = SeenLines::Approximate(bloom);
+                }
+                true
+            }
+            SeenLines::Approximate(bloom) => bloom.insert(line),
+        }
+    }
+
+    fn is_approximate(&self) -> bool {
+        matches!(self, SeenLines::Approximate(_))
+    }
+}
+
+/// Result for extract_unique_lines command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UniqueResult {
+    pub success: bool,
+    pub unique_lines: u64,
+    pub total_lines: u64,
+    pub bytes_written: u64,
+    pub duplicate_lines_removed: u64,
+    /// Set when duplicates were detected approximately, so a few unique lines may be missing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Write source_path to dest_path with repeated lines removed, keeping either the first
+/// or the last occurrence of each line. Line order is otherwise unchanged
+#[tauri::command]
+pub fn extract_unique_lines(source_path: String, dest_path: String, preserve_first: bool) -> UniqueResult {
+    let failed = |error: String| UniqueResult {
+        success: false,
+        unique_lines: 0,
+        total_lines: 0,
+        bytes_written: 0,
+        duplicate_lines_removed: 0,
+        warning: None,
+        error: Some(error),
+    };
+
+    if source_path.is_empty() || dest_path.is_empty() {
+        return failed("Invalid parameters".to_string());
+    }
+
+    let source = match File::open(&source_path) {
+        Ok(f) => f,
+        Err(_) => return failed("Cannot read file".to_string()),
+    };
+    let file_size = match source.metadata() {
+        Ok(m) => m.len(),
+        Err(_) => return failed("Cannot read file metadata".to_string()),
+    };
+
+    // Extrapolate the file's line count from what has been read so far
+    let estimate_lines = |lines: u64, bytes: u64| -> u64 {
+        if bytes == 0 {
+            lines
+        } else {
+            (file_size as u128 * lines as u128 / bytes as u128) as u64
+        }
+    };
+
+    let mut seen = SeenLines::Exact {
+        set: HashSet::new(),
+        bytes: 0,
+    };
+
+    // To keep last occurrences, walk the file backwards first: the last occurrence
+    // of a line is the first one met in reverse. keep[i] says whether line i is written
+    let keep: Option<Vec<bool>> = if preserve_first {
+        None
+    } else {
+        let mut reader = match ReverseLineReader::open(&source_path) {
+            Ok(r) => r,
+            Err(_) => return failed("Cannot read file".to_string()),
+        };
+        let mut keep = Vec::new();
+        let mut bytes_read: u64 = 0;
+        loop {
+            match reader.next_line() {
+                Ok(Some((_, line))) => {
+                    bytes_read += line.len() as u64 + 1;
+                    let estimated = estimate_lines(keep.len() as u64 + 1, bytes_read);
+                    keep.push(seen.insert(&line, estimated));
+                }
+                Ok(None) => break,
+                Err(_) => return failed("Cannot read file".to_string()),
+            }
+        }
+        keep.reverse();
+        Some(keep)
+    };
+
+    let temp = match temp_file_beside(&dest_path) {
+        Ok(t) => t,
+        Err(e) => return failed(format!("Cannot write file: {}", e)),
+    };
+
+    let mut reader = BufReader::new(source);
+    let mut writer = BufWriter::new(temp);
+    let mut total_lines: u64 = 0;
+    let mut unique_lines: u64 = 0;
+    let mut bytes_read: u64 = 0;
+    let mut bytes_written: u64 = 0;
+    let mut buf = Vec::new();
+
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) => break,
+            Ok(n) => bytes_read += n as u64,
+            Err(_) => return failed("Cannot read file".to_string()),
+        }
+
+        let idx = total_lines as usize;
+        total_lines += 1;
+
+        let is_kept = match &keep {
+            Some(keep) => keep.get(idx).copied().unwrap_or(true),
+            None => seen.insert(trim_line_ending(&buf), estimate_lines(total_lines, bytes_read)),
+        };
+        if is_kept {
+            if writer.write_all(&buf).is_err() {
+                return failed("Cannot write file".to_string());
+            }
+            unique_lines += 1;
+            bytes_written += buf.len() as u64;
+        }
+    }
+
+    let temp = match writer.into_inner() {
+        Ok(t) => t,
+        Err(_) => return failed("Cannot write file".to_string()),
+    };
+    if let Err(e) = temp.persist(&dest_path) {
+        return failed(format!("Cannot write file: {}", e.error));
+    }
+
+    let warning = if seen.is_approximate() {
+        Some("Too many unique lines to track exactly; a Bloom filter was used, so about 0.1% of unique lines may have been dropped as duplicates".to_string())
+    } else {
+        None
+    };
+
+    UniqueResult {
+        success: true,
+        unique_lines,
+        total_lines,
+        bytes_written,
+        duplicate_lines_removed: total_lines - unique_lines,
+        warning,
+        error: None,
+    }
+}
+
+/// Directory Mocha keeps its settings in, for display in the settings UI
+/// Empty if no home directory can be found and MOCHA_CONFIG_DIR is unset
+#[tauri::command]
+pub fn get_config_directory() -> String {
+    get_config_dir()
+        .map(|dir| dir.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+// Backups kept per file in the backups directory; older ones are deleted
+const MAX_BACKUPS: usize = 10;
+
+/// A backup of config.json or recent.json in the backups directory
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupMeta {
+    pub name: String,
+    pub created_at: i64,
+    pub size: u64,
+}
+
+/// Backups whose names start with prefix ("config_" or "recent_"), oldest first
+/// Names look like <prefix><unix millis>[_<label>].json
+fn list_backups(prefix: &str) -> Vec<BackupMeta> {
+    let dir = match get_backups_dir() {
+        Some(d) => d,
+        None => return vec![],
+    };
+    let entries = match fs::read_dir(&dir) {
+        Ok(e) => e,
+        Err(_) => return vec![],
+    };
+
+    let mut backups: Vec<BackupMeta> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            let stem = name.strip_prefix(prefix)?.strip_suffix(".json")?;
+            let created_at = stem.split('_').next()?.parse().ok()?;
+            let size = entry.metadata().ok()?.len();
+            Some(BackupMeta { name, created_at, size })
+        })
+        .collect();
+    backups.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.name.cmp(&b.name)));
+    backups
+}
+
+/// Copy source into the backups directory, then prune the oldest backups beyond MAX_BACKUPS
+fn backup_file(source: &Path, prefix: &str, label: Option<String>) -> bool {
+    let dir = match get_backups_dir() {
+        Some(d) => d,
+        None => return false,
+    };
+    if !source.is_file() || fs::create_dir_all(&dir).is_err() {
+        return false;
+    }
+
+    // Labels end up in a filename, so keep them to a safe character set
+    let label: String = label
+        .unwrap_or_default()
+        .trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '-' })
+        .collect();
+    let name = if label.is_empty() {
+        format!("{}{}.json", prefix, Utc::now().timestamp_millis())
+    } else {
+        format!("{}{}_{}.json", prefix, Utc::now().timestamp_millis(), label)
+    };
+
+    if fs::copy(source, dir.join(name)).is_err() {
+        return false;
+    }
+
+    let backups = list_backups(prefix);
+    let excess = backups.len().saturating_sub(MAX_BACKUPS);
+    for old in &backups[..excess] {
+        let _ = fs::remove_file(dir.join(&old.name));
+    }
+    true
+}
+
+/// Path of a backup by name, refusing names that aren't plain backups of the given kind
+fn get_backup_path(prefix: &str, backup_name: &str) -> Option<PathBuf> {
+    let is_plain_name = Path::new(backup_name).file_name().and_then(|n| n.to_str()) == Some(backup_name);
+    if !is_plain_name || !backup_name.starts_with(prefix) || !backup_name.ends_with(".json") {
+        return None;
+    }
+    get_backups_dir().map(|dir| dir.join(backup_name))
+}
+
+/// Save a copy of config.json, keeping the 10 most recent backups
+/// Holds the config lock so a half-written config is never backed up
+#[tauri::command]
+pub fn backup_config(state: State<'_, AppState>, label: Option<String>) -> bool {
+    let config_path = match get_config_path() {
+        Some(p) => p,
+        None => return false,
+    };
+    with_config_lock(&state, || backup_file(&config_path, "config_", label)).unwrap_or(false)
+}
+
+/// Replace config.json with a backup from list_config_backups and apply it
+#[tauri::command]
+pub fn restore_config(state: State<'_, AppState>, backup_name: String) -> bool {
+    let content = match get_backup_path("config_", &backup_name).and_then(|p| fs::read_to_string(p).ok()) {
+        Some(c) => c,
+        None => return false,
+    };
+    let config: Config = match serde_json::from_str(&content) {
+        Ok(c) => c,
+        Err(_) => return false,
+    };
+    let config_path = match get_config_path() {
+        Some(p) => p,
+        None => return false,
+    };
+    match with_config_lock(&state, || fs::write(&config_path, content.as_bytes())) {
+        Ok(Ok(_)) => apply_config(&state, config),
+        _ => false,
+    }
+}
+
+/// List backups of config.json and recent.json, oldest first
+#[tauri::command]
+pub fn list_config_backups() -> Vec<BackupMeta> {
+    let mut backups = list_backups("config_");
+    backups.extend(list_backups("recent_"));
+    backups.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.name.cmp(&b.name)));
+    backups
+}
+
+/// Save a copy of recent.json, keeping the 10 most recent backups
+#[tauri::command]
+pub fn backup_recent_files(label: Option<String>) -> bool {
+    match get_recent_file_path() {
+        Some(recent_path) => backup_file(&recent_path, "recent_", label),
+        None => false,
+    }
+}
+
+/// Replace the recent files list with a backup from list_config_backups
+#[tauri::command]
+pub fn restore_recent_files(state: State<'_, AppState>, backup_name: String) -> bool {
+    let files = match get_backup_path("recent_", &backup_name).and_then(|p| load_recent_files(&p)) {
+        Some(f) => f,
+        None => return false,
+    };
+
+    with_recent_files(&state, |current| {
+        *current = files;
+        ((), true)
+    })
+    .is_some()
+}
+
+// RFC 3164 (BSD) syslog, optionally without <PRI> as written to /var/log/syslog, e.g.
+// <34>Oct 11 22:14:15 mymachine su[230]: 'su root' failed for lonvick on /dev/pts/8
+// Newer rsyslog configs write an RFC 3339 timestamp in place of "Oct 11 22:14:15"
+const SYSLOG_RFC3164_PATTERN: &str = r"^(?:<(?P<pri>\d{1,3})>)?(?P<timestamp>[A-Z][a-z]{2} +\d{1,2} \d{2}:\d{2}:\d{2}|\d{4}-\d{2}-\d{2}T\S+) (?P<hostname>\S+) (?P<app>[^\s\[:]+)(?:\[(?P<pid>\d+)\])?: ?(?P<message>.*)$";
+
+// RFC 5424 syslog, e.g.
+// <165>1 2003-10-11T22:14:15.003Z mymachine evntslog 1234 ID47 [exampleSDID@32473 iut="3"] An application event
+const SYSLOG_RFC5424_PATTERN: &str = r#"^<(?P<pri>\d{1,3})>1 (?P<timestamp>\S+) (?P<hostname>\S+) (?P<app>\S+) (?P<pid>\S+) \S+ (?:-|(?:\[(?:[^\]\\"]|\\.|"(?:[^"\\]|\\.)*")*\])+)(?: (?P<message>.*))?$"#;
+
+/// A single syslog line; unparseable lines only have message (and line_number) set
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyslogEntry {
+    pub line_number: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facility: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<u8>,
+    /// Unix millis; local-time timestamps are taken as UTC
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    pub message: String,
+}
+
+/// Result for read_syslog command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyslogResult {
+    pub success: bool,
+    pub entries: Vec<SyslogEntry>,
+    pub total_lines: usize,
+    pub failed_parse_count: usize,
+    pub truncated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Parse a syslog timestamp into Unix millis
+/// BSD timestamps have no year, so the most recent matching date that isn't in the future is used
+fn parse_syslog_timestamp(text: &str) -> Option<i64> {
+    if text.starts_with(|c: char| c.is_ascii_digit()) {
+        return DateTime::parse_from_rfc3339(text).ok().map(|ts| ts.timestamp_millis());
+    }
+
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let now = Utc::now();
+    let parse_in_year = |year: i32| {
+        NaiveDateTime::parse_from_str(&format!("{} {}", year, text), "%Y %b %d %H:%M:%S")
+            .ok()
+            .map(|ts| ts.and_utc().timestamp_millis())
+    };
+
+    // Allow a day of slack for clock and timezone differences before rolling back a year
+    let ts = parse_in_year(now.year())?;
+    if ts > now.timestamp_millis() + 24 * 60 * 60 * 1000 {
+        parse_in_year(now.year() - 1)
+    } else {
+        Some(ts)
+    }
+}
+
+/// Parse one syslog line as RFC 5424 or RFC 3164; None if neither matches
+fn parse_syslog_line(rfc5424: &Regex, rfc3164: &Regex, line: &str, line_number: usize) -> Option<SyslogEntry> {
+    let caps = rfc5424.captures(line).or_else(|| rfc3164.captures(line))?;
+    // RFC 5424 uses "-" for fields with no value
+    let field = |name: &str| caps.name(name).map(|m| m.as_str()).filter(|v| *v != "-");
+
+    let pri: Option<u8> = match field("pri") {
+        Some(p) => Some(p.parse().ok().filter(|p| *p <= 191)?),
+        None => None,
+    };
+
+    Some(SyslogEntry {
+        line_number,
+        facility: pri.map(|p| p / 8),
+        severity: pri.map(|p| p % 8),
+        timestamp: field("timestamp").and_then(parse_syslog_timestamp),
+        hostname: field("hostname").map(String::from),
+        app_name: field("app").map(String::from),
+        pid: field("pid").and_then(|p| p.parse().ok()),
+        message: field("message").unwrap_or("").trim_start_matches('\u{FEFF}').to_string(),
+    })
+}
+
+/// Parse a syslog file (RFC 3164 or RFC 5424) into structured entries
+/// from_line (1-indexed) skips earlier lines; max_lines bounds how many lines are read
+#[tauri::command]
+pub fn read_syslog(path: String, from_line: Option<usize>, max_lines: Option<usize>) -> SyslogResult {
+    let failed = |error: &str| SyslogResult {
+        success: false,
+        entries: vec![],
+        total_lines: 0,
+        failed_parse_count: 0,
+        truncated: false,
+        error: Some(error.to_string()),
+    };
+
+    let (rfc5424, rfc3164) = match (Regex::new(SYSLOG_RFC5424_PATTERN), Regex::new(SYSLOG_RFC3164_PATTERN)) {
+        (Ok(a), Ok(b)) => (a, b),
+        _ => return failed("Invalid log pattern"),
+    };
+
+    let file = match File::open(&path) {
+        Ok(f) => f,
+        Err(_) => return failed("Cannot open file"),
+    };
+
+    let first_line = from_line.unwrap_or(1).max(1);
+    let mut reader = BufReader::new(file);
+    let mut entries = Vec::new();
+    let mut line_number = 0;
+    let mut total_lines = 0;
+    let mut failed_parse_count = 0;
+    let mut truncated = false;
+    let mut buf = Vec::new();
+
+    while max_lines.map_or(true, |max| total_lines < max) {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(_) => return failed("Cannot read file"),
+        }
+
+        line_number += 1;
+        if line_number < first_line {
+            continue;
+        }
+        total_lines += 1;
+
+        let line = String::from_utf8_lossy(trim_line_ending(&buf));
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let entry = parse_syslog_line(&rfc5424, &rfc3164, &line, line_number).unwrap_or_else(|| {
+            failed_parse_count += 1;
+            SyslogEntry {
+                line_number,
+                facility: None,
+                severity: None,
+                timestamp: None,
+                hostname: None,
+                app_name: None,
+                pid: None,
+                message: line.to_string(),
+            }
+        });
+
+        if entries.len() < MAX_PARSED_ENTRIES {
+            entries.push(entry);
+        } else {
+            truncated = true;
+        }
+    }
+
+    SyslogResult {
+        success: true,
+        entries,
+        total_lines,
+        failed_parse_count,
+        truncated,
+        error: None,
+    }
+}
+
+// Nginx "combined" format: CLF plus quoted referer and user agent, e.g.
+// 127.0.0.1 - - [10/Oct/2000:13:55:36 -0700] "GET / HTTP/1.1" 200 612 "-" "curl/8.0"
+const NGINX_COMBINED_PATTERN: &str = r#"^(?P<ip>\S+) \S+ (?P<user>\S+) \[(?P<timestamp>[^\]]+)\] "(?P<method>\S+) (?P<path>\S+)(?: (?P<protocol>[^"]*))?" (?P<status>\d{3}) (?P<bytes>\d+|-) "(?P<referer>(?:[^"\\]|\\.)*)" "(?P<user_agent>(?:[^"\\]|\\.)*)""#;
+
+// Number of most frequent IPs and user agents returned by parse_nginx_access_log
+const TOP_CLIENTS_LIMIT: usize = 10;
+
+/// A single parsed Nginx combined log request
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NginxLogEntry {
+    #[serde(flatten)]
+    pub request: ApacheLogEntry,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub referer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+}
+
+/// Result for parse_nginx_access_log command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NginxLogResult {
+    pub success: bool,
+    pub entries: Vec<NginxLogEntry>,
+    pub total_lines: usize,
+    pub failed_lines: usize,
+    pub truncated: bool,
+    /// Most frequent user agents and client IPs with their counts, most frequent first
+    pub top_user_agents: Vec<(String, u64)>,
+    pub top_ips: Vec<(String, u64)>,
+    /// Fraction of parsed requests with a 4xx or 5xx status
+    pub error_rate: f64,
+    pub bytes_total: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Parse an Nginx combined format access log into structured requests
+/// Statistics cover every parsed line, even past the entry cap
+#[tauri::command]
+pub fn parse_nginx_access_log(path: String, max_lines: Option<usize>) -> NginxLogResult {
+    let failed = |error: &str| NginxLogResult {
+        success: false,
+        entries: vec![],
+        total_lines: 0,
+        failed_lines: 0,
+        truncated: false,
+        top_user_agents: vec![],
+        top_ips: vec![],
+        error_rate: 0.0,
+        bytes_total: 0,
+        error: Some(error.to_string()),
+    };
+
+    let re = match Regex::new(NGINX_COMBINED_PATTERN) {
+        Ok(re) => re,
+        Err(_) => return failed("Invalid log pattern"),
+    };
+
+    let file = match File::open(&path) {
+        Ok(f) => f,
+        Err(_) => return failed("Cannot open file"),
+    };
+
+    let mut reader = BufReader::new(file);
+    let mut entries = Vec::new();
+    let mut agent_counts: HashMap<String, u64> = HashMap::new();
+    let mut ip_counts: HashMap<String, u64> = HashMap::new();
+    let mut total_lines = 0;
+    let mut failed_lines = 0;
+    let mut parsed_lines: u64 = 0;
+    let mut error_responses: u64 = 0;
+    let mut bytes_total: u64 = 0;
+    let mut truncated = false;
+    let mut buf = Vec::new();
+
+    while max_lines.map_or(true, |max| total_lines < max) {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(_) => return failed("Cannot read file"),
+        }
+
+        total_lines += 1;
+        let line = String::from_utf8_lossy(trim_line_ending(&buf));
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let entry = match re.captures(&line).and_then(|caps| {
+            // Nginx logs a missing referer or user agent as "-"
+            let quoted = |name: &str| caps.name(name).map(|m| m.as_str()).filter(|v| *v != "-").map(String::from);
+            Some(NginxLogEntry {
+                request: access_log_entry(&caps, total_lines)?,
+                referer: quoted("referer"),
+                user_agent: quoted("user_agent"),
+            })
+        }) {
+            Some(e) => e,
+            None => {
+                failed_lines += 1;
+                continue;
+            }
+        };
+
+        parsed_lines += 1;
+        if entry.request.status >= 400 {
+            error_responses += 1;
+        }
+        bytes_total += entry.request.bytes.unwrap_or(0);
+        *ip_counts.entry(entry.request.ip.clone()).or_insert(0) += 1;
+        if let Some(agent) = &entry.user_agent {
+            *agent_counts.entry(agent.clone()).or_insert(0) += 1;
+        }
+
+        if entries.len() < MAX_PARSED_ENTRIES {
+            entries.push(entry);
+        } else {
+            truncated = true;
+        }
+    }
+
+    NginxLogResult {
+        success: true,
+        entries,
+        total_lines,
+        failed_lines,
+        truncated,
+        top_user_agents: top_counts(agent_counts, TOP_CLIENTS_LIMIT),
+        top_ips: top_counts(ip_counts, TOP_CLIENTS_LIMIT),
+        error_rate: if parsed_lines > 0 { error_responses as f64 / parsed_lines as f64 } else { 0.0 },
+        bytes_total,
+        error: None,
+    }
+}
+
+/// A bookmarked log line persisted in bookmarks.json
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BookmarkEntry {
+    pub file_path: String,
+    /// 1-indexed
+    pub line_number: usize,
+    /// Line text when bookmarked, so it can be found again after the file changes
+    pub content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub created_at: i64,
+}
+
+/// Text of line line_number (1-indexed), found through the cached line index
+fn read_line_at(state: &AppState, path: &str, line_number: usize) -> Result<String, &'static str> {
+    let metadata = fs::metadata(path).map_err(|_| "Cannot open file")?;
+    let range = with_line_index(state, path, &metadata, |index| {
+        match line_number.checked_sub(1) {
+            Some(i) => index.byte_range(Path::new(path), i, i),
+            None => Ok(None),
+        }
+    })?;
+    let (start, end) = range.ok_or("Line number out of range")?;
+    let line = read_range_lossy(path, start, end - start)?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Bookmark a line, storing its current text; re-bookmarking a line updates its note
+#[tauri::command]
+pub fn create_bookmark(state: State<'_, AppState>, file_path: String, line_number: usize, note: Option<String>) -> bool {
+    let bookmarks_path = match get_bookmarks_path() {
+        Some(p) => p,
+        None => return false,
+    };
+
+    let content = match read_line_at(&state, &file_path, line_number) {
+        Ok(c) => c,
+        Err(_) => return false,
+    };
+
+    let mut bookmarks: Vec<BookmarkEntry> = load_json_list(&bookmarks_path);
+    bookmarks.retain(|b| !(b.file_path == file_path && b.line_number == line_number));
+    bookmarks.push(BookmarkEntry {
+        file_path,
+        line_number,
+        content,
+        note: note.filter(|n| !n.trim().is_empty()),
+        created_at: Utc::now().timestamp_millis(),
+    });
+
+    save_json_list(&bookmarks_path, &bookmarks)
+}
+
+/// Get all bookmarks, or only those for one file
+#[tauri::command]
+pub fn get_bookmarks(file_path: Option<String>) -> Vec<BookmarkEntry> {
+    let bookmarks: Vec<BookmarkEntry> = match get_bookmarks_path() {
+        Some(p) => load_json_list(&p),
+        None => return vec![],
+    };
+
+    match file_path {
+        Some(file_path) => bookmarks.into_iter().filter(|b| b.file_path == file_path).collect(),
+        None => bookmarks,
+    }
+}
+
+/// Delete the bookmark on a line; false if there was none
+#[tauri::command]
+pub fn delete_bookmark(file_path: String, line_number: usize) -> bool {
+    let bookmarks_path = match get_bookmarks_path() {
+        Some(p) => p,
+        None => return false,
+    };
+
+    let mut bookmarks: Vec<BookmarkEntry> = load_json_list(&bookmarks_path);
+    let before = bookmarks.len();
+    bookmarks.retain(|b| !(b.file_path == file_path && b.line_number == line_number));
+
+    if bookmarks.len() == before {
+        return false;
+    }
+    save_json_list(&bookmarks_path, &bookmarks)
+}
+
+/// Delete all bookmarks, or only those for one file
+#[tauri::command]
+pub fn clear_bookmarks(file_path: Option<String>) -> bool {
+    let bookmarks_path = match get_bookmarks_path() {
+        Some(p) => p,
+        None => return false,
+    };
+
+    let bookmarks = match file_path {
+        Some(file_path) => {
+            let mut bookmarks: Vec<BookmarkEntry> = load_json_list(&bookmarks_path);
+            bookmarks.retain(|b| b.file_path != file_path);
+            bookmarks
+        }
+        None => vec![],
+    };
+
+    save_json_list(&bookmarks_path, &bookmarks)
+}
+
+// How far from its saved line number read_bookmark looks for a moved bookmarked line
+const BOOKMARK_SEARCH_RADIUS: usize = 100;
+
+/// Find the line nearest to line_number (1-indexed) within BOOKMARK_SEARCH_RADIUS whose
+/// text matches content, first exactly and then ignoring digits (timestamps, counters)
+fn relocate_bookmark(state: &AppState, path: &str, line_number: usize, content: &str) -> Result<Option<usize>, &'static str> {
+    let metadata = fs::metadata(path).map_err(|_| "Cannot open file")?;
+    let first = line_number.saturating_sub(BOOKMARK_SEARCH_RADIUS).max(1);
+    let last = line_number.saturating_add(BOOKMARK_SEARCH_RADIUS);
+
+    let range = with_line_index(state, path, &metadata, |index| {
+        index.byte_range(Path::new(path), first - 1, last - 1)
+    })?;
+    let (start, end) = match range {
+        Some(r) => r,
+        None => return Ok(None),
+    };
+    let window = read_range_lossy(path, start, end - start)?;
+    let lines: Vec<&str> = window.lines().collect();
+
+    // Candidate line numbers ordered by distance from the saved one
+    let mut candidates: Vec<usize> = (first..first + lines.len()).collect();
+    candidates.sort_by_key(|n| n.abs_diff(line_number));
+
+    let text = |n: usize| lines[n - first].trim_end_matches('\r');
+    if let Some(n) = candidates.iter().find(|n| text(**n) == content) {
+        return Ok(Some(*n));
+    }
+    let normalized = normalize_digits(content);
+    Ok(candidates.into_iter().find(|n| normalize_digits(text(*n)) == normalized))
+}
+
+/// Read context_lines lines either side of a bookmarked line
+/// If the saved line text has moved (the file changed), the nearby line that matches it
+/// is used instead; anchor_line is None if it can't be found
+#[tauri::command]
+pub fn read_bookmark(state: State<'_, AppState>, file_path: String, line_number: usize, context_lines: usize) -> FileResult {
+    if file_path.is_empty() || line_number == 0 {
+        return FileResult::error("Invalid parameters");
+    }
+
+    let saved = get_bookmarks_path()
+        .map(|p| load_json_list::<BookmarkEntry>(&p))
+        .unwrap_or_default()
+        .into_iter()
+        .find(|b| b.file_path == file_path && b.line_number == line_number);
+
+    let anchor = match saved {
+        Some(bookmark) => match relocate_bookmark(&state, &file_path, line_number, &bookmark.content) {
+            Ok(found) => found,
+            Err(e) => return FileResult::error(e),
+        },
+        None => Some(line_number),
+    };
+
+    let result = read_file_at_line(state, file_path, anchor.unwrap_or(line_number), context_lines, context_lines);
+    let anchor_line = match (anchor, result.start_line) {
+        (Some(anchor), Some(start_line)) => Some(anchor - start_line),
+        _ => None,
+    };
+
+    FileResult { anchor_line, ..result }
+}
+
+// Default size of each of the three samples read by estimate_line_count
+const DEFAULT_ESTIMATE_SAMPLE_KB: u64 = 64;
+
+// Files smaller than this are counted exactly by estimate_line_count
+const EXACT_COUNT_THRESHOLD: u64 = 1024 * 1024;
+
+// Two-sided 95% Student's t value for three samples (two degrees of freedom)
+const T_95_TWO_DF: f64 = 4.303;
+
+/// Result for estimate_line_count command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EstimateResult {
+    pub success: bool,
+    pub estimated_lines: u64,
+    /// Rough 95% interval from the spread of line density between the samples
+    pub confidence_interval: (u64, u64),
+    pub sample_size_bytes: u64,
+    pub file_size: u64,
+    /// True if the file was small enough to count exactly
+    pub exact: bool,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Estimate a file's line count from newline density in samples at its start, middle
+/// and end. Files under 1MB, or no bigger than the samples, are counted exactly
+#[tauri::command]
+pub fn estimate_line_count(path: String, sample_size_kb: Option<u64>) -> EstimateResult {
+    let started = Instant::now();
+    let failed = |error: &str| EstimateResult {
+        success: false,
+        estimated_lines: 0,
+        confidence_interval: (0, 0),
+        sample_size_bytes: 0,
+        file_size: 0,
+        exact: false,
+        duration_ms: 0,
+        error: Some(error.to_string()),
+    };
+
+    let mut file = match File::open(&path) {
+        Ok(f) => f,
+        Err(_) => return failed("Cannot open file"),
+    };
+    let file_size = match file.metadata() {
+        Ok(m) => m.len(),
+        Err(_) => return failed("Cannot read file metadata"),
+    };
+    // Saturate so an absurd sample size just means counting the whole file
+    let window = sample_size_kb.unwrap_or(DEFAULT_ESTIMATE_SAMPLE_KB).max(1).saturating_mul(1024);
+
+    if file_size < EXACT_COUNT_THRESHOLD || file_size <= window.saturating_mul(3) {
+        let newlines = match count_newlines_before(&path, file_size) {
+            Ok(n) => n as u64,
+            Err(_) => return failed("Cannot read file"),
+        };
+        // A final line without a trailing newline still counts
+        let mut last = [0u8; 1];
+        let ends_with_newline = file_size == 0
+            || (file.seek(SeekFrom::Start(file_size - 1)).is_ok()
+                && file.read_exact(&mut last).is_ok()
+                && last[0] == b'\n');
+        let lines = newlines + u64::from(!ends_with_newline);
+
+        return EstimateResult {
+            success: true,
+            estimated_lines: lines,
+            confidence_interval: (lines, lines),
+            sample_size_bytes: file_size,
+            file_size,
+            exact: true,
+            duration_ms: started.elapsed().as_millis() as u64,
+            error: None,
+        };
+    }
+
+    // Newlines per byte in each sample
+    let mut densities = Vec::with_capacity(3);
+    let mut buf = Vec::new();
+    for (start, len) in head_middle_tail_windows(file_size, window) {
+        buf.clear();
+        if file.seek(SeekFrom::Start(start)).is_err() || (&mut file).take(len).read_to_end(&mut buf).is_err() {
+            return failed("Cannot read file");
+        }
+        densities.push(memchr::memchr_iter(b'\n', &buf).count() as f64 / buf.len().max(1) as f64);
+    }
+
+    let n = densities.len() as f64;
+    let mean = densities.iter().sum::<f64>() / n;
+    let variance = densities.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    let margin = T_95_TWO_DF * variance.sqrt() / n.sqrt();
+
+    let lines_at = |density: f64| (density.max(0.0) * file_size as f64).round() as u64;
+    EstimateResult {
+        success: true,
+        estimated_lines: lines_at(mean),
+        confidence_interval: (lines_at(mean - margin), lines_at(mean + margin)),
+        sample_size_bytes: window * 3,
+        file_size,
+        exact: false,
+        duration_ms: started.elapsed().as_millis() as u64,
+        error: None,
+    }
+}
+
+/// Result for move_recent_files_to_group command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveGroupResult {
+    pub success: bool,
+    pub moved: usize,
+    /// Paths not in the recent files list
+    pub not_found: usize,
+}
+
+/// A distinct group in the recent files list
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupSummary {
+    pub group: String,
+    pub count: usize,
+    /// Most recent last_opened among the group's files
+    pub latest_opened: i64,
+}
+
+/// Put recent files into a group in one update; an empty group removes them from their group
+#[tauri::command]
+pub fn move_recent_files_to_group(state: State<'_, AppState>, paths: Vec<String>, group: String) -> MoveGroupResult {
+    let group = group.trim();
+    let group = if group.is_empty() { None } else { Some(group.to_string()) };
+
+    let counts = with_recent_files(&state, |files| {
+        let mut moved = 0;
+        let mut not_found = 0;
+        for path in &paths {
+            match files.iter_mut().find(|f| &f.path == path) {
+                Some(entry) => {
+                    entry.group = group.clone();
+                    moved += 1;
+                }
+                None => not_found += 1,
+            }
+        }
+        ((moved, not_found), moved > 0)
+    });
+
+    match counts {
+        Some((moved, not_found)) => MoveGroupResult {
+            success: true,
+            moved,
+            not_found,
+        },
+        None => MoveGroupResult {
+            success: false,
+            moved: 0,
+            not_found: 0,
+        },
+    }
+}
+
+/// Summarize the groups in the recent files list, most recently used first
+#[tauri::command]
+pub fn get_recent_file_groups() -> Vec<GroupSummary> {
+    let recent_files = get_recent_file_path()
+        .and_then(|p| load_recent_files(&p))
+        .unwrap_or_default();
+
+    let mut groups: Vec<GroupSummary> = Vec::new();
+    for f in &recent_files {
+        let group = match &f.group {
+            Some(g) => g,
+            None => continue,
+        };
+        match groups.iter_mut().find(|g| &g.group == group) {
+            Some(summary) => {
+                summary.count += 1;
+                summary.latest_opened = summary.latest_opened.max(f.last_opened);
+            }
+            None => groups.push(GroupSummary {
+                group: group.clone(),
+                count: 1,
+                latest_opened: f.last_opened,
+            }),
+        }
+    }
+
+    groups.sort_by(|a, b| b.latest_opened.cmp(&a.latest_opened).then_with(|| a.group.cmp(&b.group)));
+    groups
+}
+
+/// Result for compress_export command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompressResult {
+    pub success: bool,
+    pub compressed_size: u64,
+    pub original_size: u64,
+    /// compressed_size / original_size
+    pub ratio: f64,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Result for decompress_file command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecompressResult {
+    pub success: bool,
+    pub compressed_size: u64,
+    pub decompressed_size: u64,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Gzip source_path into dest_path (which must end in .gz), e.g. to attach an export to a ticket
+/// level is 0 (none) to 9 (best), defaulting to zlib's usual 6. dest_path is replaced atomically
+#[tauri::command]
+pub fn compress_export(source_path: String, dest_path: String, level: Option<u32>) -> CompressResult {
+    let started = Instant::now();
+    let failed = |error: String| CompressResult {
+        success: false,
+        compressed_size: 0,
+        original_size: 0,
+        ratio: 0.0,
+        duration_ms: 0,
+        error: Some(error),
+    };
+
+    if source_path.is_empty() || !dest_path.ends_with(".gz") {
+        return failed("Destination must end in .gz".to_string());
+    }
+    let compression = match level {
+        Some(l) if l <= 9 => flate2::Compression::new(l),
+        Some(_) => return failed("Compression level must be 0-9".to_string()),
+        None => flate2::Compression::default(),
+    };
+
+    let source = match File::open(&source_path) {
+        Ok(f) => f,
+        Err(_) => return failed("Cannot read file".to_string()),
+    };
+    let temp = match temp_file_beside(&dest_path) {
+        Ok(t) => t,
+        Err(e) => return failed(format!("Cannot write file: {}", e)),
+    };
+
+    let mut encoder = flate2::write::GzEncoder::new(BufWriter::new(temp), compression);
+    let original_size = match std::io::copy(&mut BufReader::new(source), &mut encoder) {
+        Ok(n) => n,
+        Err(e) => return failed(format!("Cannot compress file: {}", e)),
+    };
+    let temp = match encoder.finish().map(|w| w.into_inner()) {
+        Ok(Ok(t)) => t,
+        _ => return failed("Cannot write file".to_string()),
+    };
+    let compressed_size = match temp.as_file().metadata() {
+        Ok(m) => m.len(),
+        Err(_) => return failed("Cannot write file".to_string()),
+    };
+    if let Err(e) = temp.persist(&dest_path) {
+        return failed(format!("Cannot write file: {}", e.error));
+    }
+
+    CompressResult {
+        success: true,
+        compressed_size,
+        original_size,
+        ratio: if original_size > 0 { compressed_size as f64 / original_size as f64 } else { 0.0 },
+        duration_ms: started.elapsed().as_millis() as u64,
+        error: None,
+    }
+}
+
+/// Decompress a gzip file into dest_path, replacing it atomically
+#[tauri::command]
+pub fn decompress_file(source_path: String, dest_path: String) -> DecompressResult {
+    let started = Instant::now();
+    let failed = |error: String| DecompressResult {
+        success: false,
+        compressed_size: 0,
+        decompressed_size: 0,
+        duration_ms: 0,
+        error: Some(error),
+    };
+
+    if source_path.is_empty() || dest_path.is_empty() {
+        return failed("Invalid parameters".to_string());
+    }
+
+    let source = match File::open(&source_path) {
+        Ok(f) => f,
+        Err(_) => return failed("Cannot read file".to_string()),
+    };
+    let compressed_size = match source.metadata() {
+        Ok(m) => m.len(),
+        Err(_) => return failed("Cannot read file metadata".to_string()),
+    };
+    let temp = match temp_file_beside(&dest_path) {
+        Ok(t) => t,
+        Err(e) => return failed(format!("Cannot write file: {}", e)),
+    };
+
+    let mut writer = BufWriter::new(temp);
+    let decompressed_size = match std::io::copy(&mut GzDecoder::new(BufReader::new(source)), &mut writer) {
+        Ok(n) => n,
+        Err(e) => return failed(format!("Cannot decompress file: {}", e)),
+    };
+    let temp = match writer.into_inner() {
+        Ok(t) => t,
+        Err(_) => return failed("Cannot write file".to_string()),
+    };
+    if let Err(e) = temp.persist(&dest_path) {
+        return failed(format!("Cannot write file: {}", e.error));
+    }
+
+    DecompressResult {
+        success: true,
+        compressed_size,
+        decompressed_size,
+        duration_ms: started.elapsed().as_millis() as u64,
+        error: None,
+    }
+}
+
+/// Per-file summary in a search_across_recent_files result
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileSearchSummary {
+    pub path: String,
+    pub name: String,
+    pub match_count: usize,
+    pub first_match_line: usize,
+    pub first_match_content: String,
+}
+
+/// Result for search_across_recent_files command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiFileSearchResult {
+    pub success: bool,
+    pub file_results: Vec<FileSearchSummary>,
+    pub total_matches: usize,
+    pub files_searched: usize,
+    pub files_with_matches: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Payload for the "cross-file-search-progress" event, emitted after each file is searched
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CrossFileSearchProgress {
+    pub path: String,
+    pub files_completed: usize,
+    pub files_total: usize,
+    pub total_matches: usize,
+}
+
+/// Stream a file counting matching lines, stopping once max_matches are found
+/// Returns the count and the first match (1-indexed line number, content)
+fn summarize_matches(path: &str, matcher: &LineMatcher, max_matches: usize) -> std::io::Result<(usize, Option<(usize, String)>)> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut match_count = 0;
+    let mut first_match = None;
+    let mut line_number = 0;
+    let mut buf = Vec::new();
+
+    while match_count < max_matches {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf)? == 0 {
+            break;
+        }
+
+        line_number += 1;
+        let line = String::from_utf8_lossy(trim_line_ending(&buf));
+        if matcher.is_match(&line) {
+            match_count += 1;
+            if first_match.is_none() {
+                first_match = Some((line_number, line.into_owned()));
+            }
+        }
+    }
+
+    Ok((match_count, first_match))
+}
+
+/// Search every existing recent file for a pattern, for when you don't remember which log had it
+/// Only files with matches are listed; max_matches_per_file caps each count (0 for no cap)
+#[tauri::command]
+pub fn search_across_recent_files(
+    app: AppHandle,
+    pattern: String,
+    use_regex: bool,
+    case_sensitive: bool,
+    max_matches_per_file: usize,
+) -> MultiFileSearchResult {
+    let failed = |error: String| MultiFileSearchResult {
+        success: false,
+        file_results: vec![],
+        total_matches: 0,
+        files_searched: 0,
+        files_with_matches: 0,
+        error: Some(error),
+    };
+
+    let matcher = match LineMatcher::new(&pattern, use_regex, case_sensitive) {
+        Ok(m) => m,
+        Err(e) => return failed(e),
+    };
+    let recent_path = match get_recent_file_path() {
+        Some(p) => p,
+        None => return failed("Cannot determine home directory".to_string()),
+    };
+
+    let mut recent_files = load_recent_files(&recent_path).unwrap_or_default();
+    refresh_recent_files(&mut recent_files);
+    recent_files.retain(|f| f.exists);
+
+    let max_matches = if max_matches_per_file == 0 { usize::MAX } else { max_matches_per_file };
+    let files_total = recent_files.len();
+    let mut file_results = Vec::new();
+    let mut total_matches = 0;
+    let mut files_searched = 0;
+
+    for (i, f) in recent_files.into_iter().enumerate() {
+        // Unreadable files are left out rather than failing the whole search
+        if let Ok((match_count, first_match)) = summarize_matches(&f.path, &matcher, max_matches) {
+            files_searched += 1;
+            total_matches += match_count;
+            if let Some((first_match_line, first_match_content)) = first_match {
+                file_results.push(FileSearchSummary {
+                    path: f.path.clone(),
+                    name: f.name,
+                    match_count,
+                    first_match_line,
+                    first_match_content,
+                });
+            }
+        }
+
+        let _ = app.emit("cross-file-search-progress", CrossFileSearchProgress {
+            path: f.path,
+            files_completed: i + 1,
+            files_total,
+            total_matches,
+        });
+    }
+
+    MultiFileSearchResult {
+        success: true,
+        files_with_matches: file_results.len(),
+        file_results,
+        total_matches,
+        files_searched,
+        error: None,
+    }
+}
+
+/// Result for parse_csv_log command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CsvLogResult {
+    pub success: bool,
+    pub entries: Vec<HashMap<String, String>>,
+    pub headers: Vec<String>,
+    pub parsed_lines: usize,
+    pub failed_lines: usize,
+    pub truncated: bool,
+    /// Entries per distinct value of level_column, when one was given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level_counts: Option<HashMap<String, u64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Parse a CSV-formatted log (e.g. timestamp,level,thread,logger,message) into field maps
+/// Keys come from the header row, or col_0, col_1, ... without one; columns renames them in order
+#[tauri::command]
+pub fn parse_csv_log(
+    path: String,
+    has_header: bool,
+    delimiter: Option<char>,
+    columns: Option<Vec<String>>,
+    level_column: Option<String>,
+) -> CsvLogResult {
+    let failed = |error: &str| CsvLogResult {
+        success: false,
+        entries: vec![],
+        headers: vec![],
+        parsed_lines: 0,
+        failed_lines: 0,
+        truncated: false,
+        level_counts: None,
+        error: Some(error.to_string()),
+    };
+
+    let delimiter = match delimiter {
+        None => b',',
+        Some(c) if c.is_ascii() => c as u8,
+        Some(_) => return failed("Delimiter must be a single ASCII character"),
+    };
+
+    let file = match File::open(&path) {
+        Ok(f) => f,
+        Err(_) => return failed("Cannot open file"),
+    };
+
+    // Rows may have more or fewer fields than the header; extra fields get col_N keys
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(has_header)
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(BufReader::new(file));
+
+    let mut headers: Vec<String> = if has_header {
+        match reader.headers() {
+            Ok(h) => h.iter().map(|s| s.to_string()).collect(),
+            Err(_) => return failed("Cannot read header row"),
+        }
+    } else {
+        vec![]
+    };
+    let renames = columns.unwrap_or_default();
+    let column_name = |headers: &[String], i: usize| {
+        renames.get(i)
+            .or_else(|| headers.get(i))
+            .cloned()
+            .unwrap_or_else(|| format!("col_{}", i))
+    };
+
+    let mut entries = Vec::new();
+    let mut parsed_lines = 0;
+    let mut failed_lines = 0;
+    let mut truncated = false;
+    let mut level_counts = level_column.as_ref().map(|_| HashMap::new());
+    // Widest row seen, so headerless files still report every column
+    let mut width = headers.len();
+
+    for record in reader.records() {
+        let record = match record {
+            Ok(r) => r,
+            Err(_) => {
+                failed_lines += 1;
+                continue;
+            }
+        };
+
+        parsed_lines += 1;
+        width = width.max(record.len());
+        let entry: HashMap<String, String> = record.iter()
+            .enumerate()
+            .map(|(i, value)| (column_name(&headers, i), value.to_string()))
+            .collect();
+
+        if let (Some(counts), Some(column)) = (level_counts.as_mut(), level_column.as_ref()) {
+            if let Some(level) = entry.get(column) {
+                *counts.entry(level.clone()).or_insert(0u64) += 1;
+            }
+        }
+
+        if entries.len() < MAX_PARSED_ENTRIES {
+            entries.push(entry);
+        } else {
+            truncated = true;
+        }
+    }
+
+    headers = (0..width).map(|i| column_name(&headers, i)).collect();
+
+    CsvLogResult {
+        success: true,
+        entries,
+        headers,
+        parsed_lines,
+        failed_lines,
+        truncated,
+        level_counts,
+        error: None,
+    }
+}
+
+/// One non-empty bucket in a get_timeline_data result
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeBucket {
+    /// Bucket boundary in Unix millis
+    pub ts: i64,
+    pub count: u64,
+}
+
+/// Result for get_timeline_data command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineResult {
+    pub success: bool,
+    pub buckets: Vec<TimeBucket>,
+    pub total_parsed: u64,
+    pub parse_failures: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub earliest_ts: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest_ts: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Count log lines per time bucket for an activity heatmap
+/// Each line's timestamp (strptime pattern) is rounded to the nearest bucket_size_seconds
+/// boundary; only non-empty buckets are returned, oldest first
+#[tauri::command]
+pub fn get_timeline_data(path: String, timestamp_pattern: String, bucket_size_seconds: u64) -> TimelineResult {
+    let failed = |error: &str| TimelineResult {
+        success: false,
+        buckets: vec![],
+        total_parsed: 0,
+        parse_failures: 0,
+        earliest_ts: None,
+        latest_ts: None,
+        error: Some(error.to_string()),
+    };
+
+    if timestamp_pattern.is_empty() || bucket_size_seconds == 0 {
+        return failed("Invalid parameters");
+    }
+    let bucket_ms = match i64::try_from(bucket_size_seconds).ok().and_then(|s| s.checked_mul(1000)) {
+        Some(ms) => ms,
+        None => return failed("Bucket size too large"),
+    };
+
+    let file = match File::open(&path) {
+        Ok(f) => f,
+        Err(_) => return failed("Cannot open file"),
+    };
+
+    let mut reader = BufReader::new(file);
+    let mut counts: BTreeMap<i64, u64> = BTreeMap::new();
+    let mut total_parsed = 0;
+    let mut parse_failures = 0;
+    let mut earliest_ts: Option<i64> = None;
+    let mut latest_ts: Option<i64> = None;
+    let mut buf = Vec::new();
+
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(_) => return failed("Cannot read file"),
+        }
+
+        let line = String::from_utf8_lossy(trim_line_ending(&buf));
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let ts = match parse_line_timestamp(&line, &timestamp_pattern) {
+            Some(ts) => ts,
+            None => {
+                parse_failures += 1;
+                continue;
+            }
+        };
+
+        total_parsed += 1;
+        earliest_ts = Some(earliest_ts.map_or(ts, |e| e.min(ts)));
+        latest_ts = Some(latest_ts.map_or(ts, |l| l.max(ts)));
+
+        let bucket = ts.saturating_add(bucket_ms / 2).div_euclid(bucket_ms) * bucket_ms;
+        *counts.entry(bucket).or_insert(0) += 1;
+    }
+
+    TimelineResult {
+        success: true,
+        buckets: counts.into_iter().map(|(ts, count)| TimeBucket { ts, count }).collect(),
+        total_parsed,
+        parse_failures,
+        earliest_ts,
+        latest_ts,
+        error: None,
+    }
+}
+
+// Pattern counted as an error line when get_error_rate is given an empty one
+const DEFAULT_ERROR_PATTERN: &str = "ERROR";
+
+/// Result for get_error_rate command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorRateResult {
+    pub success: bool,
+    pub error_count: u64,
+    pub total_count: u64,
+    /// error_count / total_count, 0 for an empty window
+    pub rate: f64,
+    /// Earliest and latest timestamps (Unix millis) covered, when a timestamp pattern was given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window_start: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window_end: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Fraction of non-empty lines matching error_pattern (case-insensitive substring, "ERROR" if empty)
+/// With window_seconds, only the last window_seconds of the log (by timestamp_pattern) are counted;
+/// lines without a timestamp belong to the timestamped line above them
+#[tauri::command]
+pub fn get_error_rate(
+    path: String,
+    window_seconds: Option<u64>,
+    timestamp_pattern: Option<String>,
+    error_pattern: String,
+) -> ErrorRateResult {
+    let failed = |error: &str| ErrorRateResult {
+        success: false,
+        error_count: 0,
+        total_count: 0,
+        rate: 0.0,
+        window_start: None,
+        window_end: None,
+        error: Some(error.to_string()),
+    };
+
+    let error_pattern = if error_pattern.is_empty() { DEFAULT_ERROR_PATTERN.to_string() } else { error_pattern };
+    let matcher = match LineMatcher::new(&error_pattern, false, false) {
+        Ok(m) => m,
+        Err(e) => return failed(&e),
+    };
+    let timestamp_pattern = timestamp_pattern.filter(|p| !p.is_empty());
+
+    let mut error_count = 0;
+    let mut total_count = 0;
+    let mut window_start = None;
+    let mut window_end = None;
+
+    if let Some(window_seconds) = window_seconds {
+        let pattern = match &timestamp_pattern {
+            Some(p) => p,
+            None => return failed("A timestamp pattern is needed to analyze a time window"),
+        };
+        let window_ms = i64::try_from(window_seconds).unwrap_or(i64::MAX).saturating_mul(1000);
+
+        // Walk back from the end, stopping at the first timestamp before the window;
+        // this assumes the log is written in time order
+        let mut reader = match ReverseLineReader::open(&path) {
+            Ok(r) => r,
+            Err(_) => return failed("Cannot open file"),
+        };
+        // Untimestamped lines seen since the last timestamp, as (errors, total)
+        let mut pending = (0, 0);
+        loop {
+            let line = match reader.next_line() {
+                Ok(Some((_, line))) => line,
+                Ok(None) => break,
+                Err(_) => return failed("Cannot read file"),
+            };
+            let line = String::from_utf8_lossy(&line);
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let is_error = matcher.is_match(&line) as u64;
+            let ts = match parse_line_timestamp(&line, pattern) {
+                Some(ts) => ts,
+                None => {
+                    pending.0 += is_error;
+                    pending.1 += 1;
+                    continue;
+                }
+            };
+
+            let end = *window_end.get_or_insert(ts);
+            if ts < end.saturating_sub(window_ms) {
+                break;
+            }
+            window_start = Some(window_start.map_or(ts, |s: i64| s.min(ts)));
+            error_count += pending.0 + is_error;
+            total_count += pending.1 + 1;
+            pending = (0, 0);
+        }
+
+        if window_end.is_none() {
+            return failed("No timestamps found");
+        }
+    } else {
+        let file = match File::open(&path) {
+            Ok(f) => f,
+            Err(_) => return failed("Cannot open file"),
+        };
+        let mut reader = BufReader::new(file);
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            match reader.read_until(b'\n', &mut buf) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(_) => return failed("Cannot read file"),
+            }
+
+            let line = String::from_utf8_lossy(trim_line_ending(&buf));
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            total_count += 1;
+            if matcher.is_match(&line) {
+                error_count += 1;
+            }
+            if let Some(ts) = timestamp_pattern.as_deref().and_then(|p| parse_line_timestamp(&line, p)) {
+                window_start = Some(window_start.map_or(ts, |s: i64| s.min(ts)));
+                window_end = Some(window_end.map_or(ts, |e: i64| e.max(ts)));
+            }
+        }
+    }
+
+    ErrorRateResult {
+        success: true,
+        error_count,
+        total_count,
+        rate: if total_count > 0 { error_count as f64 / total_count as f64 } else { 0.0 },
+        window_start,
+        window_end,
+        error: None,
+    }
+}
+
+/// A matched span within one line of returned content
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HighlightSpan {
+    /// 0-indexed line within content
+    pub line_number: usize,
+    /// Span in Unicode scalar values from the start of the line, end exclusive
+    pub char_start: usize,
+    pub char_end: usize,
+}
+
+/// Result for read_file_with_highlights command: a read_file result plus match spans
+#[derive(Serialize)]
+pub struct HighlightedFileResult {
+    #[serde(flatten)]
+    pub file: FileResult,
+    pub highlights: Vec<HighlightSpan>,
+}
+
+/// Find every non-empty match of re in content, as per-line char spans
+fn highlight_spans(content: &str, re: &Regex) -> Vec<HighlightSpan> {
+    let mut spans = Vec::new();
+    for (line_number, line) in content.lines().enumerate() {
+        // Count chars incrementally so each line is only walked once
+        let mut byte_pos = 0;
+        let mut char_pos = 0;
+        for m in re.find_iter(line).filter(|m| !m.is_empty()) {
+            char_pos += line[byte_pos..m.start()].chars().count();
+            let char_len = m.as_str().chars().count();
+            spans.push(HighlightSpan {
+                line_number,
+                char_start: char_pos,
+                char_end: char_pos + char_len,
+            });
+            byte_pos = m.end();
+            char_pos += char_len;
+        }
+    }
+    spans
+}
+
+/// read_file, plus the spans matching pattern so the frontend needn't re-scan the content
+#[tauri::command]
+pub fn read_file_with_highlights(
+    window: Window,
+    state: State<'_, AppState>,
+    path: String,
+    offset: u64,
+    pattern: String,
+    use_regex: bool,
+    case_sensitive: bool,
+) -> HighlightedFileResult {
+    let failed = |error: &str| HighlightedFileResult {
+        file: FileResult::error(error),
+        highlights: vec![],
+    };
+
+    if pattern.is_empty() {
+        return failed("Empty pattern");
+    }
+    let pattern = if use_regex { pattern } else { regex::escape(&pattern) };
+    let re = match RegexBuilder::new(&pattern).case_insensitive(!case_sensitive).build() {
+        Ok(re) => re,
+        Err(e) => return failed(&format!("Invalid regex: {}", e)),
+    };
+
+    let file = read_file(window, state, path, offset, None, false, None);
+    let highlights = file.content.as_deref()
+        .map(|content| highlight_spans(content, &re))
+        .unwrap_or_default();
+
+    HighlightedFileResult { file, highlights }
+}
+
+/// Result for resolve_symlinks_in_recent_files command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveResult {
+    pub success: bool,
+    /// Entries whose stored path changed to its canonical form
+    pub resolved: usize,
+    pub duplicates_removed: usize,
+    /// Entries kept under their original path because it couldn't be resolved
+    pub unresolvable: usize,
+}
+
+/// Canonicalize every stored recent file path, then drop entries that turn out to be
+/// the same file (e.g. a symlink and its target), keeping the most recently opened one
+#[tauri::command]
+pub fn resolve_symlinks_in_recent_files(state: State<'_, AppState>) -> ResolveResult {
+    let counts = with_recent_files(&state, |files| {
+        let mut resolved = 0;
+        let mut unresolvable = 0;
+        for f in files.iter_mut() {
+            let normalized = normalize_path(f.path.clone());
+            if normalized.error.is_some() {
+                unresolvable += 1;
+            } else if normalized.changed {
+                f.name = get_filename(&normalized.canonical);
+                f.path = normalized.canonical;
+                resolved += 1;
+            }
+        }
+
+        // Most recently opened wins; the survivors keep their place in the list
+        let mut latest: HashMap<String, i64> = HashMap::new();
+        for f in files.iter() {
+            let opened = latest.entry(f.path.clone()).or_insert(f.last_opened);
+            *opened = (*opened).max(f.last_opened);
+        }
+        let before = files.len();
+        files.retain(|f| match latest.get(&f.path) {
+            // Remove it so a duplicate with the same timestamp isn't kept too
+            Some(&opened) if opened == f.last_opened => {
+                latest.remove(&f.path);
+                true
+            }
+            _ => false,
+        });
+        let duplicates_removed = before - files.len();
+
+        ((resolved, duplicates_removed, unresolvable), resolved > 0 || duplicates_removed > 0)
+    });
+
+    match counts {
+        Some((resolved, duplicates_removed, unresolvable)) => ResolveResult {
+            success: true,
+            resolved,
+            duplicates_removed,
+            unresolvable,
+        },
+        None => ResolveResult {
+            success: false,
+            resolved: 0,
+            duplicates_removed: 0,
+            unresolvable: 0,
+        },
+    }
+}
+
+/// A note attached to a log line, persisted in annotations.json
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Annotation {
+    pub file_path: String,
+    /// 1-indexed
+    pub line_number: usize,
+    /// May span several lines
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Line text when annotated, to tell whether the line has since changed
+    pub content: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+    /// Set by get_annotations when the line no longer holds content (e.g. after rotation)
+    #[serde(default)]
+    pub stale: bool,
+}
+
+/// Add or replace the annotation on a line, storing the line's current text
+/// Replacing keeps the original created_at
+#[tauri::command]
+pub fn write_annotation(
+    state: State<'_, AppState>,
+    file_path: String,
+    line_number: usize,
+    text: String,
+    author: Option<String>,
+) -> bool {
+    if text.trim().is_empty() {
+        return false;
+    }
+
+    let annotations_path = match get_annotations_path() {
+        Some(p) => p,
+        None => return false,
+    };
+
+    let content = match read_line_at(&state, &file_path, line_number) {
+        Ok(c) => c,
+        Err(_) => return false,
+    };
+
+    let now = Utc::now().timestamp_millis();
+    let mut annotations: Vec<Annotation> = load_json_list(&annotations_path);
+    let author = author.filter(|a| !a.trim().is_empty());
+
+    match annotations.iter_mut().find(|a| a.file_path == file_path && a.line_number == line_number) {
+        Some(existing) => {
+            existing.text = text;
+            existing.author = author;
+            existing.content = content;
+            existing.updated_at = now;
+        }
+        None => annotations.push(Annotation {
+            file_path,
+            line_number,
+            text,
+            author,
+            content,
+            created_at: now,
+            updated_at: now,
+            stale: false,
+        }),
+    }
+
+    save_json_list(&annotations_path, &annotations)
+}
+
+/// Get a file's annotations sorted by line number, flagging those whose line has changed
+#[tauri::command]
+pub fn get_annotations(state: State<'_, AppState>, file_path: String) -> Vec<Annotation> {
+    let annotations: Vec<Annotation> = match get_annotations_path() {
+        Some(p) => load_json_list(&p),
+        None => return vec![],
+    };
+
+    let mut annotations: Vec<Annotation> = annotations.into_iter()
+        .filter(|a| a.file_path == file_path)
+        .collect();
+    annotations.sort_by_key(|a| a.line_number);
+
+    for a in annotations.iter_mut() {
+        a.stale = read_line_at(&state, &a.file_path, a.line_number).map_or(true, |line| line != a.content);
+    }
+    annotations
+}
+
+/// Delete the annotation on a line; false if there was none
+#[tauri::command]
+pub fn delete_annotation(file_path: String, line_number: usize) -> bool {
+    let annotations_path = match get_annotations_path() {
+        Some(p) => p,
+        None => return false,
+    };
+
+    let mut annotations: Vec<Annotation> = load_json_list(&annotations_path);
+    let before = annotations.len();
+    annotations.retain(|a| !(a.file_path == file_path && a.line_number == line_number));
+
+    if annotations.len() == before {
+        return false;
+    }
+    save_json_list(&annotations_path, &annotations)
+}
+
+/// Escape text for inclusion in HTML element content or attribute values
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Start of a standalone export page with embedded dark-theme CSS, up to where the
+/// escaped log lines go; HTML_EXPORT_END closes it
+fn html_export_start(name: &str, generated_at: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{name}</title>
+<style>
+  body {{ margin: 0; background: #1e1e1e; color: #d4d4d4; font-family: ui-monospace, Menlo, Consolas, monospace; }}
+  header {{ padding: 12px 16px; border-bottom: 1px solid #333; }}
+  h1 {{ margin: 0; font-size: 16px; color: #fff; }}
+  .generated {{ margin-top: 4px; font-size: 12px; color: #888; }}
+  pre {{ margin: 0; padding: 12px 0; font-size: 13px; line-height: 1.45; }}
+  .line {{ display: block; padding: 0 16px; white-space: pre-wrap; word-break: break-all; }}
+  .ln {{ display: inline-block; min-width: 6ch; margin-right: 16px; color: #666; text-align: right; user-select: none; }}
+  .highlight {{ background: #4b3b00; color: #ffd866; }}
+</style>
+</head>
+<body>
+<header>
+<h1>{name}</h1>
+<div class="generated">Generated {generated_at}</div>
+</header>
+<pre>"#
+    )
+}
+
+// Closes the page started by html_export_start
+const HTML_EXPORT_END: &str = "</pre>\n</body>\n</html>\n";
+
+/// Export a log as a self-contained HTML page, for sharing with people without Mocha
+/// Only the last max_lines lines are included when set, located with the cached line
+/// index; lines matching the highlight_pattern regex are highlighted.
+/// The page is streamed to a temp file that replaces dest_path atomically
+#[tauri::command]
+pub fn export_as_html(
+    state: State<'_, AppState>,
+    source_path: String,
+    dest_path: String,
+    highlight_pattern: Option<String>,
+    max_lines: Option<usize>,
+) -> ExportResult {
+    let failed = |error: String| ExportResult {
+        success: false,
+        created_dirs: false,
+        bytes_written: 0,
+        bom_written: false,
+        error: Some(error),
+    };
+
+    if source_path.is_empty() || dest_path.is_empty() {
+        return failed("No path provided".to_string());
+    }
+
+    let matcher = match highlight_pattern.as_deref().filter(|p| !p.is_empty()) {
+        Some(pattern) => match LineMatcher::new(pattern, true, true) {
+            Ok(m) => Some(m),
+            Err(e) => return failed(e),
+        },
+        None => None,
+    };
+
+    let metadata = match fs::metadata(&source_path) {
+        Ok(m) => m,
+        Err(_) => return failed("Cannot read file".to_string()),
+    };
+
+    // Where the exported lines start, and the file line number of the first one
+    let (start, first_line_number) = match max_lines {
+        Some(max_lines) => {
+            let located = with_line_index(&state, &source_path, &metadata, |index| {
+                let first = index.line_count().saturating_sub(max_lines);
+                let start = index.byte_range(Path::new(&source_path), first, first)?
+                    .map_or(index.size, |(start, _)| start);
+                Ok((start, first + 1))
+            });
+            match located {
+                Ok(l) => l,
+                Err(e) => return failed(e.to_string()),
+            }
+        }
+        None => (0, 1),
+    };
+
+    let mut file = match File::open(&source_path) {
+        Ok(f) => f,
+        Err(_) => return failed("Cannot read file".to_string()),
+    };
+    if file.seek(SeekFrom::Start(start)).is_err() {
+        return failed("Cannot read file".to_string());
+    }
+    // Stop at the size seen above so lines appended meanwhile don't skew the numbering
+    let mut reader = BufReader::new(file.take(metadata.len().saturating_sub(start)));
+
+    let temp = match temp_file_beside(&dest_path) {
+        Ok(t) => t,
+        Err(e) => return failed(format!("Cannot write file: {}", e)),
+    };
+    let mut writer = BufWriter::new(temp);
+
+    let page_start = html_export_start(&escape_html(&get_filename(&source_path)), &Utc::now().to_rfc3339());
+    if let Err(e) = writer.write_all(page_start.as_bytes()) {
+        return failed(format!("Cannot write file: {}", e));
+    }
+    let mut bytes_written = page_start.len() as u64;
+
+    // Lines are escaped and written one at a time so the page is never held in memory
+    let mut buf = Vec::new();
+    let mut line_number = first_line_number;
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(_) => return failed("Cannot read file".to_string()),
+        }
+        let line = String::from_utf8_lossy(trim_line_ending(&buf));
+        let escaped = escape_html(&line);
+        let text = match &matcher {
+            Some(m) if m.is_match(&line) => format!("<span class=\"highlight\">{}</span>", escaped),
+            _ => escaped,
+        };
+        let html = format!("<span class=\"line\"><span class=\"ln\">{}</span>{}</span>", line_number, text);
+        if let Err(e) = writer.write_all(html.as_bytes()) {
+            return failed(format!("Cannot write file: {}", e));
+        }
+        bytes_written += html.len() as u64;
+        line_number += 1;
+    }
+
+    if let Err(e) = writer.write_all(HTML_EXPORT_END.as_bytes()) {
+        return failed(format!("Cannot write file: {}", e));
+    }
+    bytes_written += HTML_EXPORT_END.len() as u64;
+
+    let temp = match writer.into_inner() {
+        Ok(t) => t,
+        Err(_) => return failed("Cannot write file".to_string()),
+    };
+    if let Err(e) = temp.persist(&dest_path) {
+        return failed(format!("Cannot write file: {}", e.error));
+    }
+
+    ExportResult {
+        success: true,
+        created_dirs: false,
+        bytes_written,
+        bom_written: false,
+        error: None,
+    }
+}
+
+// How long set_config and backup_config wait for another instance to release config.lock
+const CONFIG_LOCK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
+
+// Pause between attempts to take config.lock
+const CONFIG_LOCK_RETRY_INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
+
+/// Take an exclusive advisory lock on an open file without blocking
+#[cfg(unix)]
+fn try_lock_file(file: &File) -> bool {
+    use std::os::unix::io::AsRawFd;
+    unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) == 0 }
+}
+
+/// Advisory locks aren't supported here, so locking always succeeds
+#[cfg(not(unix))]
+fn try_lock_file(_file: &File) -> bool {
+    true
+}
+
+/// Open config.lock and lock it, retrying until CONFIG_LOCK_TIMEOUT
+/// The lock is released when the returned file is dropped
+fn acquire_config_lock() -> Result<File, String> {
+    let lock_path = get_config_lock_path().ok_or("Cannot determine home directory")?;
+    if let Some(parent) = lock_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Cannot create config directory: {}", e))?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .map_err(|e| format!("Cannot open config lock: {}", e))?;
+
+    let started = Instant::now();
+    while !try_lock_file(&file) {
+        if started.elapsed() >= CONFIG_LOCK_TIMEOUT {
+            return Err("Config is locked by another instance".to_string());
+        }
+        std::thread::sleep(CONFIG_LOCK_RETRY_INTERVAL);
+    }
+    Ok(file)
+}
+
+/// Run f while holding the config lock, reusing the one from lock_config if held
+fn with_config_lock<T>(state: &AppState, f: impl FnOnce() -> T) -> Result<T, String> {
+    // Held for the whole call so commands in this instance also take turns
+    let held = state.config_lock.lock().map_err(|_| "Config lock unavailable".to_string())?;
+    if held.is_some() {
+        return Ok(f());
+    }
+    let _lock = acquire_config_lock()?;
+    Ok(f())
+}
+
+/// Make config the active configuration, resizing the line index cache to match
+fn apply_config(state: &AppState, config: Config) -> bool {
+    if let Ok(mut cache) = state.line_index_cache.lock() {
+        cache.resize(config.line_index_capacity());
+    }
+    match state.config.write() {
+        Ok(mut current) => {
+            *current = config;
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// Take the advisory lock on config.lock until unlock_config, so other
+/// Mocha instances can't write config.json meanwhile
+#[tauri::command]
+pub fn lock_config(state: State<'_, AppState>) -> bool {
+    let mut held = match state.config_lock.lock() {
+        Ok(h) => h,
+        Err(_) => return false,
+    };
+    if held.is_some() {
+        return true;
+    }
+    match acquire_config_lock() {
+        Ok(file) => {
+            *held = Some(file);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// Release the lock taken by lock_config; false if it wasn't held
+#[tauri::command]
+pub fn unlock_config(state: State<'_, AppState>) -> bool {
+    match state.config_lock.lock() {
+        Ok(mut held) => held.take().is_some(),
+        Err(_) => false,
+    }
+}
+
+/// Result for set_config command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetConfigResult {
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Save config to config.json under the config lock and apply it
+/// Fails if another instance holds the lock for more than a second
+#[tauri::command]
+pub fn set_config(state: State<'_, AppState>, config: Config) -> SetConfigResult {
+    let failed = |error: String| SetConfigResult {
+        success: false,
+        error: Some(error),
+    };
+
+    let config_path = match get_config_path() {
+        Some(p) => p,
+        None => return failed("Cannot determine home directory".to_string()),
+    };
+    let json = match serde_json::to_string_pretty(&config) {
+        Ok(j) => j,
+        Err(e) => return failed(format!("Cannot serialize config: {}", e)),
+    };
+
+    match with_config_lock(&state, || fs::write(&config_path, json.as_bytes())) {
+        Ok(Ok(_)) => {}
+        Ok(Err(e)) => return failed(format!("Cannot write config: {}", e)),
+        Err(e) => return failed(e),
+    }
+
+    if !apply_config(&state, config) {
+        return failed("Cannot apply config".to_string());
+    }
+    SetConfigResult {
+        success: true,
+        error: None,
+    }
+}
+
+// Read size for get_file_character_stats
+const CHAR_STATS_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Result for get_file_character_stats command
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CharStatsResult {
+    pub success: bool,
+    /// ASCII characters, including control characters and NULs
+    pub ascii_count: u64,
+    /// Validly encoded non-ASCII characters
+    pub multibyte_count: u64,
+    pub null_byte_count: u64,
+    /// U+0000 to U+001F other than \n, \r and \t
+    pub control_char_count: u64,
+    pub line_count: u64,
+    /// In characters, not counting line endings
+    pub max_line_length: usize,
+    pub avg_line_length: f64,
+    /// Invalid UTF-8 sequences, each decoded as one U+FFFD
+    pub replacement_char_count: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Running totals for get_file_character_stats
+#[derive(Default)]
+struct CharStats {
+    result: CharStatsResult,
+    line_length: usize,
+    total_line_length: u64,
+}
+
+impl CharStats {
+    fn add_char(&mut self, c: char) {
+        match c {
+            '\n' => {
+                self.end_line();
+                self.result.ascii_count += 1;
+                return;
+            }
+            // Part of a line ending, not the line
+            '\r' => {}
+            _ => self.line_length += 1,
+        }
+
+        if c.is_ascii() {
+            self.result.ascii_count += 1;
+        } else {
+            self.result.multibyte_count += 1;
+        }
+        if c == '\0' {
+            self.result.null_byte_count += 1;
+        }
+        if c < '\u{20}' && !matches!(c, '\r' | '\t') {
+            self.result.control_char_count += 1;
+        }
+    }
+
+    fn add_invalid(&mut self) {
+        self.result.replacement_char_count += 1;
+        self.line_length += 1;
+    }
+
+    fn end_line(&mut self) {
+        self.result.line_count += 1;
+        self.result.max_line_length = self.result.max_line_length.max(self.line_length);
+        self.total_line_length += self.line_length as u64;
+        self.line_length = 0;
+    }
+}
+
+/// Count character categories across a file, for debugging encoding problems
+/// Decodes UTF-8 incrementally in 64KB chunks, so sequences split across chunks are kept whole
+#[tauri::command]
+pub fn get_file_character_stats(path: String) -> CharStatsResult {
+    let failed = |error: &str| CharStatsResult {
+        error: Some(error.to_string()),
+        ..Default::default()
+    };
+
+    let mut file = match File::open(&path) {
+        Ok(f) => f,
+        Err(_) => return failed("Cannot read file"),
+    };
+
+    let mut stats = CharStats::default();
+    let mut chunk = vec![0u8; CHAR_STATS_CHUNK_SIZE];
+    // Bytes of an incomplete UTF-8 sequence left over from the previous chunk
+    let mut pending: Vec<u8> = Vec::new();
+    let mut ends_with_newline = true;
+
+    loop {
+        let n = match file.read(&mut chunk) {
+            Ok(n) => n,
+            Err(_) => return failed("Cannot read file"),
+        };
+        let at_eof = n == 0;
+        pending.extend_from_slice(&chunk[..n]);
+
+        let mut rest: &[u8] = &pending;
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(valid) => {
+                    valid.chars().for_each(|c| stats.add_char(c));
+                    rest = &[];
+                    break;
+                }
+                Err(e) => {
+                    let (valid, after) = rest.split_at(e.valid_up_to());
+                    std::str::from_utf8(valid).unwrap_or_default().chars().for_each(|c| stats.add_char(c));
+                    match e.error_len() {
+                        Some(len) => {
+                            stats.add_invalid();
+                            rest = &after[len..];
+                        }
+                        // Truncated sequence: wait for the next chunk unless the file has ended
+                        None if !at_eof => {
+                            rest = after;
+                            break;
+                        }
+                        None => {
+                            stats.add_invalid();
+                            rest = &[];
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        pending = rest.to_vec();
+
+        if at_eof {
+            break;
+        }
+        ends_with_newline = chunk[n - 1] == b'\n';
+    }
+
+    // A last line without a trailing newline still counts
+    if !ends_with_newline {
+        stats.end_line();
+    }
+
+    let mut result = stats.result;
+    result.success = true;
+    result.avg_line_length = if result.line_count > 0 {
+        stats.total_line_length as f64 / result.line_count as f64
+    } else {
+        0.0
+    };
+    result
+}
+
+/// Result for get_file_growth_rate command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrowthResult {
+    pub success: bool,
+    pub start_size: u64,
+    pub end_size: u64,
+    /// Negative if the file was truncated during the sample
+    pub bytes_added: i64,
+    pub rate_bytes_per_sec: f64,
+    pub sample_duration_ms: u64,
+    /// From the average line length at the start of the file; None if it has no full line yet
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines_added_estimate: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Average bytes per line over the first SAMPLE_WINDOW_SIZE bytes of a file
+fn leading_bytes_per_line(path: &str) -> Option<f64> {
+    let file = File::open(path).ok()?;
+    let mut sample = Vec::new();
+    file.take(SAMPLE_WINDOW_SIZE).read_to_end(&mut sample).ok()?;
+
+    // Only complete lines give a fair average
+    let last_newline = memchr::memrchr(b'\n', &sample)?;
+    let lines = memchr::memchr_iter(b'\n', &sample).count();
+    Some((last_newline + 1) as f64 / lines as f64)
+}
+
+/// Measure how fast a file grows over sample_duration_ms, for capacity planning
+/// The wait happens on a background thread so IPC isn't blocked
+#[tauri::command]
+pub async fn get_file_growth_rate(path: String, sample_duration_ms: u64) -> GrowthResult {
+    let failed = |error: &str| GrowthResult {
+        success: false,
+        start_size: 0,
+        end_size: 0,
+        bytes_added: 0,
+        rate_bytes_per_sec: 0.0,
+        sample_duration_ms,
+        lines_added_estimate: None,
+        error: Some(error.to_string()),
+    };
+
+    if path.is_empty() || sample_duration_ms == 0 {
+        return failed("Invalid parameters");
+    }
+
+    let sample = tauri::async_runtime::spawn_blocking(move || {
+        let start_size = fs::metadata(&path).ok()?.len();
+        let started = Instant::now();
+        std::thread::sleep(std::time::Duration::from_millis(sample_duration_ms));
+        let end_size = fs::metadata(&path).ok()?.len();
+        let elapsed = started.elapsed();
+        Some((start_size, end_size, elapsed, leading_bytes_per_line(&path)))
+    })
+    .await;
+
+    let (start_size, end_size, elapsed, bytes_per_line) = match sample {
+        Ok(Some(s)) => s,
+        _ => return failed("Cannot open file"),
+    };
+
+    let bytes_added = end_size as i64 - start_size as i64;
+    let lines_added_estimate = match bytes_per_line {
+        Some(per_line) if bytes_added >= 0 => Some((bytes_added as f64 / per_line).round() as u64),
+        _ => None,
+    };
+
+    GrowthResult {
+        success: true,
+        start_size,
+        end_size,
+        bytes_added,
+        // Use the measured time; sleep may overshoot the requested duration
+        rate_bytes_per_sec: bytes_added as f64 / elapsed.as_secs_f64(),
+        sample_duration_ms,
+        lines_added_estimate,
+        error: None,
+    }
+}
+
+/// Field remapping applied to each record by apply_log_transform
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LogTransform {
+    /// Old key to new key, e.g. "@timestamp" to "timestamp"
+    pub field_renames: HashMap<String, String>,
+    pub drop_fields: Vec<String>,
+    /// Constant fields added to every record, replacing any existing value
+    pub add_fields: HashMap<String, serde_json::Value>,
+    pub pretty_print: bool,
+}
+
+/// Result for apply_log_transform command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransformResult {
+    pub success: bool,
+    pub lines_transformed: u64,
+    pub lines_failed: u64,
+    pub bytes_written: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Rename, then drop, then add fields on one record
+fn transform_record(
+    mut record: serde_json::Map<String, serde_json::Value>,
+    transform: &LogTransform,
+) -> serde_json::Map<String, serde_json::Value> {
+    for (from, to) in &transform.field_renames {
+        if let Some(value) = record.remove(from) {
+            record.insert(to.clone(), value);
+        }
+    }
+    for field in &transform.drop_fields {
+        record.remove(field);
+    }
+    for (field, value) in &transform.add_fields {
+        record.insert(field.clone(), value.clone());
+    }
+    record
+}
+
+/// Stream an NDJSON log through a field transform into output_path, e.g. to give
+/// logs from different services the same field names
+/// Lines that aren't JSON objects are left out and counted in lines_failed
+#[tauri::command]
+pub fn apply_log_transform(path: String, transform: LogTransform, output_path: String) -> TransformResult {
+    let failed = |error: String| TransformResult {
+        success: false,
+        lines_transformed: 0,
+        lines_failed: 0,
+        bytes_written: 0,
+        error: Some(error),
+    };
+
+    if path.is_empty() || output_path.is_empty() {
+        return failed("No path provided".to_string());
+    }
+
+    let file = match File::open(&path) {
+        Ok(f) => f,
+        Err(_) => return failed("Cannot open file".to_string()),
+    };
+    let temp = match temp_file_beside(&output_path) {
+        Ok(t) => t,
+        Err(e) => return failed(format!("Cannot write file: {}", e)),
+    };
+
+    let mut reader = BufReader::new(file);
+    let mut writer = BufWriter::new(temp);
+    let mut lines_transformed = 0;
+    let mut lines_failed = 0;
+    let mut bytes_written = 0;
+    let mut buf = Vec::new();
+
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(_) => return failed("Cannot read file".to_string()),
+        }
+
+        let line = String::from_utf8_lossy(trim_line_ending(&buf));
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let record = match parse_json_line(&line) {
+            Some(obj) => transform_record(obj, &transform),
+            None => {
+                lines_failed += 1;
+                continue;
+            }
+        };
+
+        let json = if transform.pretty_print {
+            serde_json::to_string_pretty(&record)
+        } else {
+            serde_json::to_string(&record)
+        };
+        let mut json = match json {
+            Ok(j) => j,
+            Err(_) => {
+                lines_failed += 1;
+                continue;
+            }
+        };
+        json.push('\n');
+
+        if let Err(e) = writer.write_all(json.as_bytes()) {
+            return failed(format!("Cannot write file: {}", e));
+        }
+        lines_transformed += 1;
+        bytes_written += json.len() as u64;
+    }
+
+    let temp = match writer.into_inner() {
+        Ok(t) => t,
+        Err(_) => return failed("Cannot write file".to_string()),
+    };
+    if let Err(e) = temp.persist(&output_path) {
+        return failed(format!("Cannot write file: {}", e.error));
+    }
+
+    TransformResult {
+        success: true,
+        lines_transformed,
+        lines_failed,
+        bytes_written,
+        error: None,
+    }
+}
+
+// Pause between polls of a named pipe that has no data ready
+#[cfg(all(unix, feature = "unix-pipes"))]
+const PIPE_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(10);
+
+/// Read from a FIFO without blocking, until max_bytes are read or no data has
+/// arrived for timeout_ms (including when no writer has the pipe open)
+#[cfg(all(unix, feature = "unix-pipes"))]
+fn read_pipe_bytes(path: &str, timeout_ms: u64, max_bytes: u64) -> Result<Vec<u8>, String> {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let metadata = fs::metadata(path).map_err(|_| "Cannot open file".to_string())?;
+    if !is_fifo(&metadata) {
+        return Err("Not a named pipe".to_string());
+    }
+
+    let mut pipe = OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(path)
+        .map_err(|e| format!("Cannot open pipe: {}", e))?;
+
+    let timeout = std::time::Duration::from_millis(timeout_ms);
+    let mut data = Vec::new();
+    let mut chunk = [0u8; 64 * 1024];
+    let mut last_data = Instant::now();
+
+    // Keep reading back to back while data is flowing; only wait when the pipe is empty
+    while (data.len() as u64) < max_bytes {
+        let want = chunk.len().min((max_bytes - data.len() as u64) as usize);
+        match pipe.read(&mut chunk[..want]) {
+            Ok(n) if n > 0 => {
+                data.extend_from_slice(&chunk[..n]);
+                last_data = Instant::now();
+                continue;
+            }
+            // 0 means no writer is connected right now; one may still open the pipe
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {}
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(format!("Cannot read pipe: {}", e)),
+        }
+        if last_data.elapsed() >= timeout {
+            break;
+        }
+        std::thread::sleep(PIPE_POLL_INTERVAL);
+    }
+
+    Ok(data)
+}
+
+#[cfg(all(not(unix), feature = "unix-pipes"))]
+fn read_pipe_bytes(_path: &str, _timeout_ms: u64, _max_bytes: u64) -> Result<Vec<u8>, String> {
+    Err("Named pipes not supported on this platform".to_string())
+}
+
+#[cfg(not(feature = "unix-pipes"))]
+fn read_pipe_bytes(_path: &str, _timeout_ms: u64, _max_bytes: u64) -> Result<Vec<u8>, String> {
+    Err("Named pipe support is not enabled in this build".to_string())
+}
+
+/// Read whatever is available from a named pipe (FIFO), for logs streamed through one
+/// Stops at max_bytes or once no data has arrived for timeout_ms. Needs the unix-pipes feature
+#[tauri::command]
+pub async fn read_pipe(path: String, timeout_ms: u64, max_bytes: u64) -> FileResult {
+    if path.is_empty() || max_bytes == 0 {
+        return FileResult::error("Invalid parameters");
+    }
+
+    let read_path = path.clone();
+    let read = tauri::async_runtime::spawn_blocking(move || read_pipe_bytes(&read_path, timeout_ms, max_bytes)).await;
+    let data = match read {
+        Ok(Ok(d)) => d,
+        Ok(Err(e)) => return FileResult::error(&e),
+        Err(_) => return FileResult::error("Read task failed"),
+    };
+
+    let content = String::from_utf8_lossy(&data).into_owned();
+    let replacement_char_count = count_replacements(&content);
+    FileResult {
+        success: true,
+        content: Some(content),
+        name: Some(get_filename(&path)),
+        display_name: get_filename(&path),
+        path: Some(path),
+        size: Some(data.len() as u64),
+        truncated: Some(data.len() as u64 >= max_bytes),
+        replacement_char_count: Some(replacement_char_count),
+        error: encoding_warning(replacement_char_count),
+        ..Default::default()
+    }
+}
+
+// Directory name for recent files whose path has no parent, e.g. bare file names
+const UNKNOWN_DIRECTORY: &str = "(unknown)";
+
+/// Recent files sharing a parent directory
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryGroup {
+    pub directory: String,
+    /// Most recently opened first
+    pub files: Vec<RecentFile>,
+    pub latest_opened: i64,
+    pub file_count: usize,
+}
+
+/// Recent files grouped by parent directory, most recently used directory first
+#[tauri::command]
+pub fn group_recent_files_by_directory() -> Vec<DirectoryGroup> {
+    let mut recent_files = get_recent_file_path()
+        .and_then(|p| load_recent_files(&p))
+        .unwrap_or_default();
+    refresh_recent_files(&mut recent_files);
+
+    let mut groups: Vec<DirectoryGroup> = Vec::new();
+    for f in recent_files {
+        let directory = Path::new(&f.path)
+            .parent()
+            .filter(|d| !d.as_os_str().is_empty())
+            .and_then(|d| d.to_str())
+            .unwrap_or(UNKNOWN_DIRECTORY)
+            .to_string();
+        match groups.iter_mut().find(|g| g.directory == directory) {
+            Some(group) => {
+                group.latest_opened = group.latest_opened.max(f.last_opened);
+                group.file_count += 1;
+                group.files.push(f);
+            }
+            None => groups.push(DirectoryGroup {
+                directory,
+                latest_opened: f.last_opened,
+                file_count: 1,
+                files: vec![f],
+            }),
+        }
+    }
+
+    for group in groups.iter_mut() {
+        group.files.sort_by_key(|f| Reverse(f.last_opened));
+    }
+    groups.sort_by(|a, b| b.latest_opened.cmp(&a.latest_opened).then_with(|| a.directory.cmp(&b.directory)));
+    groups
+}
+
+// by_extension key for recent files whose name has no extension
+const NO_EXTENSION: &str = "(none)";
+
+/// Summary of the recent files list
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentFilesStats {
+    pub total_count: usize,
+    pub exists_count: usize,
+    pub missing_count: usize,
+    pub pinned_count: usize,
+    /// Sum of the stored sizes of files that exist
+    pub total_size_bytes: u64,
+    pub oldest_last_opened: Option<i64>,
+    pub newest_last_opened: Option<i64>,
+    /// File count per lowercased extension
+    pub by_extension: HashMap<String, usize>,
+    pub avg_open_count: f64,
+}
+
+/// Statistics over the recent files list, from the size and exists values stored in
+/// recent.json (no filesystem calls, so they may be stale)
+#[tauri::command]
+pub fn get_recent_files_statistics() -> RecentFilesStats {
+    let recent_files = get_recent_file_path()
+        .and_then(|p| load_recent_files(&p))
+        .unwrap_or_default();
+
+    let mut stats = RecentFilesStats {
+        total_count: recent_files.len(),
+        exists_count: 0,
+        missing_count: 0,
+        pinned_count: 0,
+        total_size_bytes: 0,
+        oldest_last_opened: None,
+        newest_last_opened: None,
+        by_extension: HashMap::new(),
+        avg_open_count: 0.0,
+    };
+    let mut total_open_count = 0u64;
+
+    for f in &recent_files {
+        if f.exists {
+            stats.exists_count += 1;
+            stats.total_size_bytes += f.size.unwrap_or(0);
+        } else {
+            stats.missing_count += 1;
+        }
+        if f.is_pinned {
+            stats.pinned_count += 1;
+        }
+        stats.oldest_last_opened = Some(stats.oldest_last_opened.map_or(f.last_opened, |t| t.min(f.last_opened)));
+        stats.newest_last_opened = Some(stats.newest_last_opened.map_or(f.last_opened, |t| t.max(f.last_opened)));
+        total_open_count += f.open_count as u64;
+
+        let extension = Path::new(&f.path)
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_else(|| NO_EXTENSION.to_string());
+        *stats.by_extension.entry(extension).or_insert(0) += 1;
+    }
+
+    if !recent_files.is_empty() {
+        stats.avg_open_count = total_open_count as f64 / recent_files.len() as f64;
+    }
+    stats
+}
+
+/// Set the note on a recent file; blank notes clear it. False if the path isn't in the list
+#[tauri::command]
+pub fn set_recent_file_notes(state: State<'_, AppState>, path: String, notes: String) -> bool {
+    let notes = notes.trim();
+    with_recent_files(&state, |files| match files.iter_mut().find(|f| f.path == path) {
+        Some(entry) => {
+            entry.notes = if notes.is_empty() { None } else { Some(notes.to_string()) };
+            (true, true)
+        }
+        None => (false, false),
+    })
+    .unwrap_or(false)
+}
+
+/// The fields of a recent.json entry needed to look up its note
+#[derive(Deserialize)]
+struct RecentFileNote {
+    path: String,
+    #[serde(default)]
+    notes: Option<String>,
+}
+
+/// Visits recent.json's entries one at a time looking for a path's note
+struct FindRecentFileNote<'a>(&'a str);
+
+impl<'de> serde::de::Visitor<'de> for FindRecentFileNote<'_> {
+    type Value = Option<String>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a list of recent files")
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        while let Some(entry) = seq.next_element::<RecentFileNote>()? {
+            if entry.path == self.0 {
+                // The rest still has to be consumed, but is skipped without being parsed into values
+                while seq.next_element::<serde::de::IgnoredAny>()?.is_some() {}
+                return Ok(entry.notes);
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Get the note on a recent file, if it has one
+/// recent.json is scanned entry by entry, so the full list is never loaded
+#[tauri::command]
+pub fn get_recent_file_notes(path: String) -> Option<String> {
+    let file = File::open(get_recent_file_path()?).ok()?;
+    let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(file));
+    serde::Deserializer::deserialize_seq(&mut deserializer, FindRecentFileNote(&path)).ok()?
+}
+
+/// Prefix each line with its number, right-aligned to the widest number, e.g. "  4823 | ..."
+fn number_lines(content: &str, first_line_number: usize) -> String {
+    let line_count = content.split_inclusive('\n').count();
+    let last_line_number = first_line_number + line_count.saturating_sub(1);
+    let width = last_line_number.to_string().len();
+
+    let mut numbered = String::with_capacity(content.len() + line_count * (width + 3));
+    for (i, line) in content.split_inclusive('\n').enumerate() {
+        numbered.push_str(&format!("{:>width$} | {}", first_line_number + i, line, width = width));
+    }
+    numbered
+}
+
+/// read_file with each line prefixed by its line number, for sharing excerpts
+/// start_line_number is the number to give the first returned line, since a
+/// tail or polling read doesn't know where in the file it starts
+#[tauri::command]
+pub fn read_file_with_line_numbers(
+    window: Window,
+    state: State<'_, AppState>,
+    path: String,
+    offset: u64,
+    start_line_number: usize,
+) -> FileResult {
+    let mut result = read_file(window, state, path, offset, None, false, None);
+    if let Some(content) = result.content.take() {
+        let line_count = content.split_inclusive('\n').count();
+        if line_count > 0 {
+            result.start_line = Some(start_line_number);
+            result.end_line = Some(start_line_number + line_count - 1);
+        }
+        result.lines_returned = Some(line_count);
+        result.content = Some(number_lines(&content, start_line_number));
+    }
+    result
+}
+
+/// Result for get_disk_space command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskSpaceResult {
+    pub success: bool,
+    /// Bytes available to the current user
+    pub free_bytes: u64,
+    pub total_bytes: u64,
+    pub used_bytes: u64,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Space on the filesystem that holds path, e.g. to warn before exporting to a nearly full disk
+/// path needn't exist yet; its nearest existing ancestor is checked instead
+#[tauri::command]
+pub fn get_disk_space(path: String) -> DiskSpaceResult {
+    let dir = Path::new(&path)
+        .ancestors()
+        .find(|a| a.as_os_str().is_empty() || a.exists())
+        .map(|a| if a.as_os_str().is_empty() { Path::new(".") } else { a })
+        .unwrap_or(Path::new("."));
+
+    match disk_space(dir) {
+        Some(space) => DiskSpaceResult {
+            success: true,
+            free_bytes: space.free,
+            total_bytes: space.total,
+            used_bytes: space.used,
+            path,
+            error: None,
+        },
+        None => DiskSpaceResult {
+            success: false,
+            free_bytes: 0,
+            total_bytes: 0,
+            used_bytes: 0,
+            path,
+            error: Some("Cannot read disk space".to_string()),
+        },
+    }
+}
+
+/// A log line found by find_log_errors_near_timestamp
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntry {
+    /// 1-indexed
+    pub line_number: usize,
+    /// None for lines without their own timestamp, e.g. stack trace lines
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<i64>,
+    pub content: String,
+    /// Text matched by the level pattern, e.g. "ERROR"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<String>,
+}
+
+/// Result for find_log_errors_near_timestamp command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IncidentSearchResult {
+    pub success: bool,
+    pub entries: Vec<LogEntry>,
+    pub searched_lines: u64,
+    pub total_matched: u64,
+    pub truncated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Lines matching level_pattern (a case-insensitive regex such as "ERROR|WARN") within
+/// window_seconds either side of timestamp (Unix millis), for incident correlation
+/// Lines without a timestamp take the one above them. Results are oldest first
+#[tauri::command]
+pub fn find_log_errors_near_timestamp(
+    path: String,
+    timestamp: i64,
+    window_seconds: u64,
+    timestamp_pattern: String,
+    level_pattern: String,
+) -> IncidentSearchResult {
+    let failed = |error: String| IncidentSearchResult {
+        success: false,
+        entries: vec![],
+        searched_lines: 0,
+        total_matched: 0,
+        truncated: false,
+        error: Some(error),
+    };
+
+    if timestamp_pattern.is_empty() {
+        return failed("Invalid parameters".to_string());
+    }
+    let level_re = match RegexBuilder::new(&level_pattern).case_insensitive(true).build() {
+        Ok(re) if !level_pattern.is_empty() => re,
+        Ok(_) => return failed("Empty pattern".to_string()),
+        Err(e) => return failed(format!("Invalid regex: {}", e)),
+    };
+
+    let window_ms = i64::try_from(window_seconds).unwrap_or(i64::MAX).saturating_mul(1000);
+    let window = timestamp.saturating_sub(window_ms)..=timestamp.saturating_add(window_ms);
+
+    let file = match File::open(&path) {
+        Ok(f) => f,
+        Err(_) => return failed("Cannot open file".to_string()),
+    };
+
+    let mut reader = BufReader::new(file);
+    // Matches paired with the timestamp they were filtered on
+    let mut matched: Vec<(i64, LogEntry)> = Vec::new();
+    let mut searched_lines = 0;
+    let mut total_matched = 0;
+    let mut last_ts: Option<i64> = None;
+    let mut buf = Vec::new();
+
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(_) => return failed("Cannot read file".to_string()),
+        }
+
+        searched_lines += 1;
+        let line = String::from_utf8_lossy(trim_line_ending(&buf));
+        let own_ts = parse_line_timestamp(&line, &timestamp_pattern);
+        if own_ts.is_some() {
+            last_ts = own_ts;
+        }
+
+        let effective_ts = match last_ts {
+            Some(ts) if window.contains(&ts) => ts,
+            _ => continue,
+        };
+        let level = match level_re.find(&line) {
+            Some(m) => m.as_str().to_string(),
+            None => continue,
+        };
+
+        total_matched += 1;
+        if matched.len() < MAX_PARSED_ENTRIES {
+            matched.push((effective_ts, LogEntry {
+                line_number: searched_lines as usize,
+                timestamp: own_ts,
+                content: line.into_owned(),
+                level: Some(level),
+            }));
+        }
+    }
+
+    // Stable, so lines sharing a timestamp stay in file order
+    matched.sort_by_key(|(ts, _)| *ts);
+
+    IncidentSearchResult {
+        success: true,
+        truncated: total_matched > matched.len() as u64,
+        entries: matched.into_iter().map(|(_, entry)| entry).collect(),
+        searched_lines,
+        total_matched,
+        error: None,
+    }
+}
+
+/// Display name for path: relative to base when path is inside it, else the file name
+/// Tells apart e.g. app/server.log and db/server.log under /var/log
+pub fn display_name_relative_to(path: &str, base: &str) -> String {
+    match Path::new(path).strip_prefix(base) {
+        Ok(relative) if !relative.as_os_str().is_empty() => relative.to_string_lossy().into_owned(),
+        _ => get_filename(path),
+    }
+}
+
+/// Optional settings for read_file_with_options; see read_file for each
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ReadFileOptions {
+    pub strip_prefix_regex: Option<String>,
+    pub normalize_crlf: bool,
+    pub expected_inode: Option<u64>,
+    /// Base directory for display_name, for files whose names alone are ambiguous
+    pub relative_to: Option<String>,
+}
+
+/// read_file with its optional settings grouped in one object
+#[tauri::command]
+pub fn read_file_with_options(
+    window: Window,
+    state: State<'_, AppState>,
+    path: String,
+    offset: u64,
+    options: Option<ReadFileOptions>,
+) -> FileResult {
+    let options = options.unwrap_or_default();
+    let display_name = options.relative_to
+        .filter(|base| !base.is_empty())
+        .map(|base| display_name_relative_to(&path, &base));
+
+    let mut result = read_file(
+        window,
+        state,
+        path,
+        offset,
+        options.strip_prefix_regex,
+        options.normalize_crlf,
+        options.expected_inode,
+    );
+    if let Some(display_name) = display_name.filter(|_| result.success) {
+        result.display_name = display_name;
+    }
+    result
+}
+
+/// Result for apply_regex_filter_export command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineResult {
+    pub success: bool,
+    pub lines_written: u64,
+    pub lines_read: u64,
+    /// Lines matching at least one include pattern
+    pub include_matched: u64,
+    /// Included lines then dropped by an exclude pattern
+    pub exclude_removed: u64,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Compile every pattern up front so a bad one is reported before any reading
+fn compile_patterns(patterns: &[String], kind: &str) -> Result<Vec<Regex>, String> {
+    patterns.iter()
+        .enumerate()
+        .map(|(i, p)| Regex::new(p).map_err(|e| format!("Invalid {} pattern {}: {}", kind, i + 1, e)))
+        .collect()
+}
+
+/// Export lines matching any include regex and no exclude regex, with context_lines
+/// of context around each. No include patterns means every line is included
+/// Overlapping context windows are written once; dest is replaced atomically
+#[tauri::command]
+pub fn apply_regex_filter_export(
+    source: String,
+    dest: String,
+    include_patterns: Vec<String>,
+    exclude_patterns: Vec<String>,
+    context_lines: usize,
+) -> PipelineResult {
+    let started = Instant::now();
+    let failed = |error: String| PipelineResult {
+        success: false,
+        lines_written: 0,
+        lines_read: 0,
+        include_matched: 0,
+        exclude_removed: 0,
+        duration_ms: 0,
+        error: Some(error),
+    };
+
+    if source.is_empty() || dest.is_empty() {
+        return failed("Invalid parameters".to_string());
+    }
+
+    let includes = match compile_patterns(&include_patterns, "include") {
+        Ok(r) => r,
+        Err(e) => return failed(e),
+    };
+    let excludes = match compile_patterns(&exclude_patterns, "exclude") {
+        Ok(r) => r,
+        Err(e) => return failed(e),
+    };
+
+    let source_file = match File::open(&source) {
         Ok(f) => f,
-        Err(_) => {
-            return FileResult {
-                success: false,
-                content: None,
-                path: None,
-                name: None,
-                size: None,
-                prev_size: None,
-                mtime: None,
-                truncated: None,
-                error: Some("Cannot open file".to_string()),
-            };
-        }
+        Err(_) => return failed("Cannot read file".to_string()),
+    };
+    let temp = match temp_file_beside(&dest) {
+        Ok(t) => t,
+        Err(e) => return failed(format!("Cannot write file: {}", e)),
     };
 
-    // Seek to read position
-    if actual_read_start > 0 {
-        if file.seek(SeekFrom::Start(actual_read_start)).is_err() {
-            return FileResult {
-                success: false,
-                content: None,
-                path: None,
-                name: None,
-                size: None,
-                prev_size: None,
-                mtime: None,
-                truncated: None,
-                error: Some("Cannot seek in file".to_string()),
-            };
+    let mut reader = BufReader::new(source_file);
+    let mut writer = BufWriter::new(temp);
+    let mut before: VecDeque<(u64, Vec<u8>)> = VecDeque::with_capacity(context_lines);
+    let mut remaining_after = 0;
+    // First line index not yet written, so overlapping windows are not repeated
+    let mut next_unwritten: u64 = 0;
+    let mut lines_read: u64 = 0;
+    let mut include_matched: u64 = 0;
+    let mut exclude_removed: u64 = 0;
+    let mut lines_written: u64 = 0;
+    let mut buf = Vec::new();
+
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(_) => return failed("Cannot read file".to_string()),
         }
-    }
 
-    // Read content
-    let mut content = vec![0u8; read_size as usize];
-    if file.read_exact(&mut content).is_err() {
-        // Try reading what we can
-        let mut file = File::open(&path).unwrap();
-        file.seek(SeekFrom::Start(actual_read_start)).unwrap();
-        content.clear();
-        file.read_to_end(&mut content).ok();
-    }
+        let idx = lines_read;
+        lines_read += 1;
 
-    // For tail reads, skip partial first line (we may have started mid-line)
-    let content_str = if is_tail_read {
-        let s = String::from_utf8_lossy(&content);
-        // Find first newline and skip everything before it
-        if let Some(pos) = s.find('\n') {
-            s[pos + 1..].to_string()
+        let line = String::from_utf8_lossy(trim_line_ending(&buf));
+        let included = includes.is_empty() || includes.iter().any(|re| re.is_match(&line));
+        let is_match = if included {
+            include_matched += 1;
+            let excluded = excludes.iter().any(|re| re.is_match(&line));
+            if excluded {
+                exclude_removed += 1;
+            }
+            !excluded
         } else {
-            s.to_string()
+            false
+        };
+
+        let mut to_write: Vec<Vec<u8>> = Vec::new();
+        if is_match {
+            to_write.extend(
+                before
+                    .drain(..)
+                    .filter(|(i, _)| *i >= next_unwritten)
+                    .map(|(_, line)| line),
+            );
+            to_write.push(buf.clone());
+            remaining_after = context_lines;
+        } else if remaining_after > 0 {
+            to_write.push(buf.clone());
+            remaining_after -= 1;
+        } else if context_lines > 0 {
+            if before.len() == context_lines {
+                before.pop_front();
+            }
+            before.push_back((idx, buf.clone()));
         }
-    } else {
-        String::from_utf8_lossy(&content).to_string()
+
+        if !to_write.is_empty() {
+            for line in &to_write {
+                if writer.write_all(line).is_err() {
+                    return failed("Cannot write file".to_string());
+                }
+                lines_written += 1;
+            }
+            next_unwritten = idx + 1;
+        }
+    }
+
+    let temp = match writer.into_inner() {
+        Ok(t) => t,
+        Err(_) => return failed("Cannot write file".to_string()),
     };
+    if let Err(e) = temp.persist(&dest) {
+        return failed(format!("Cannot write file: {}", e.error));
+    }
 
-    FileResult {
+    PipelineResult {
         success: true,
-        content: Some(content_str),
-        path: Some(path.clone()),
-        name: Some(get_filename(&path)),
-        size: Some(current_size),
-        prev_size: Some(offset),
-        mtime,
-        truncated: Some(is_truncated || is_tail_read),
+        lines_written,
+        lines_read,
+        include_matched,
+        exclude_removed,
+        duration_ms: started.elapsed().as_millis() as u64,
         error: None,
     }
 }
 
-/// Get list of recently opened files
-#[tauri::command]
-pub fn get_recent_files() -> Vec<RecentFile> {
-    let path = match get_recent_file_path() {
-        Some(p) => p,
-        None => return vec![],
-    };
+/// Result for run_lua_filter command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LuaFilterResult {
+    pub success: bool,
+    pub lines_matched: u64,
+    pub lines_processed: u64,
+    /// Matching lines, when no output_path was given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    /// True when output stopped at MAX_READ_SIZE; lines_matched still counts every match
+    pub truncated: bool,
+    pub execution_time_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
 
-    if !path.exists() {
-        return vec![];
-    }
+// Longest a filter script may run over a whole file before it is stopped
+#[cfg(feature = "lua-filters")]
+const LUA_FILTER_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
 
-    let content = match fs::read_to_string(&path) {
-        Ok(c) => c,
-        Err(_) => return vec![],
-    };
+// Lua VM instructions between checks of LUA_FILTER_TIMEOUT
+#[cfg(feature = "lua-filters")]
+const LUA_HOOK_INSTRUCTIONS: u32 = 10_000;
 
-    let files: Vec<RecentFile> = match serde_json::from_str(&content) {
-        Ok(f) => f,
-        Err(_) => return vec![],
-    };
+// Memory a filter script may allocate, so e.g. string.rep can't exhaust the app's memory
+#[cfg(feature = "lua-filters")]
+const LUA_MEMORY_LIMIT: usize = 64 * 1024 * 1024;
 
-    // Refresh mtime, size, and exists from filesystem for each file
-    files.into_iter().map(|mut f| {
-        if let Ok(metadata) = fs::metadata(&f.path) {
-            f.exists = true;
-            f.size = Some(metadata.len());
-            f.mtime = metadata.modified()
-                .ok()
-                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-                .map(|d| d.as_millis() as i64);
+/// Run a Lua script's filter(line, line_num) over each line of a file (line_num is
+/// 1-indexed), passing the lines it returns true for to emit
+/// Returns (lines_matched, lines_processed)
+#[cfg(feature = "lua-filters")]
+fn lua_filter_lines(
+    path: &str,
+    script: &str,
+    mut emit: impl FnMut(&[u8]) -> std::io::Result<()>,
+) -> Result<(u64, u64), String> {
+    use mlua::{Function, HookTriggers, Lua, LuaOptions, StdLib, Value, VmState};
+
+    // Sandboxed: only the pure libraries, no io, os, package or debug
+    let lua = Lua::new_with(StdLib::STRING | StdLib::TABLE | StdLib::MATH | StdLib::UTF8, LuaOptions::new())
+        .map_err(|e| format!("Cannot start Lua: {}", e))?;
+    lua.set_memory_limit(LUA_MEMORY_LIMIT)
+        .map_err(|e| format!("Cannot start Lua: {}", e))?;
+    let deadline = Instant::now() + LUA_FILTER_TIMEOUT;
+    lua.set_hook(HookTriggers::new().every_nth_instruction(LUA_HOOK_INSTRUCTIONS), move |_, _| {
+        if Instant::now() > deadline {
+            Err(mlua::Error::runtime("script took too long"))
         } else {
-            f.exists = false;
-            f.size = None;
-            f.mtime = None;
+            Ok(VmState::Continue)
+        }
+    });
+
+    let globals = lua.globals();
+    // The base library can still read files through dofile and loadfile, and print
+    // writes to the app's stdout
+    for name in ["dofile", "loadfile", "print"] {
+        globals.set(name, Value::Nil).map_err(|e| format!("Cannot start Lua: {}", e))?;
+    }
+
+    // Compile and run the script before touching the file so script errors come first
+    lua.load(script)
+        .set_name("filter")
+        .exec()
+        .map_err(|e| format!("Invalid script: {}", e))?;
+    let filter: Function = globals.get("filter")
+        .map_err(|_| "Script must define function filter(line, line_num)".to_string())?;
+
+    let file = File::open(path).map_err(|_| "Cannot read file".to_string())?;
+    let mut reader = BufReader::new(file);
+    let mut lines_matched: u64 = 0;
+    let mut lines_processed: u64 = 0;
+    let mut buf = Vec::new();
+
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(_) => return Err("Cannot read file".to_string()),
         }
-        f
-    }).collect()
+
+        lines_processed += 1;
+        let line = String::from_utf8_lossy(trim_line_ending(&buf));
+        let keep: bool = filter.call((&*line, lines_processed))
+            .map_err(|e| format!("Script error on line {}: {}", lines_processed, e))?;
+        if keep {
+            emit(&buf).map_err(|_| "Cannot write file".to_string())?;
+            lines_matched += 1;
+        }
+    }
+
+    Ok((lines_matched, lines_processed))
 }
 
-/// Add a file to the recent files list
+#[cfg(not(feature = "lua-filters"))]
+fn lua_filter_lines(
+    _path: &str,
+    _script: &str,
+    _emit: impl FnMut(&[u8]) -> std::io::Result<()>,
+) -> Result<(u64, u64), String> {
+    Err("Lua filters are not enabled in this build".to_string())
+}
+
+/// Keep the lines of a file for which a Lua script's filter(line, line_num) returns true
+/// Matching lines are returned in output (up to MAX_READ_SIZE), or written to output_path
+/// (replaced atomically). The script can't use io, os or other libraries that reach
+/// outside it, and is stopped past LUA_FILTER_TIMEOUT or LUA_MEMORY_LIMIT. Needs the lua-filters feature
 #[tauri::command]
-pub fn add_recent_file(path: String) -> bool {
-    if path.is_empty() {
-        return false;
+pub async fn run_lua_filter(path: String, script: String, output_path: Option<String>) -> LuaFilterResult {
+    let started = Instant::now();
+    let run = tauri::async_runtime::spawn_blocking(move || lua_filter_file(&path, &script, output_path.as_deref())).await;
+    match run {
+        Ok(result) => result,
+        Err(_) => LuaFilterResult {
+            success: false,
+            lines_matched: 0,
+            lines_processed: 0,
+            output: None,
+            truncated: false,
+            execution_time_ms: started.elapsed().as_millis() as u64,
+            error: Some("Filter task failed".to_string()),
+        },
     }
+}
 
-    let recent_path = match get_recent_file_path() {
-        Some(p) => p,
-        None => return false,
+/// Blocking body of run_lua_filter
+fn lua_filter_file(path: &str, script: &str, output_path: Option<&str>) -> LuaFilterResult {
+    let started = Instant::now();
+    let failed = |error: String| LuaFilterResult {
+        success: false,
+        lines_matched: 0,
+        lines_processed: 0,
+        output: None,
+        truncated: false,
+        execution_time_ms: started.elapsed().as_millis() as u64,
+        error: Some(error),
     };
 
-    // Create ~/.mocha directory if needed
-    if let Some(parent) = recent_path.parent() {
-        if !parent.exists() {
-            if fs::create_dir_all(parent).is_err() {
-                return false;
+    if path.is_empty() || script.is_empty() {
+        return failed("Invalid parameters".to_string());
+    }
+
+    let mut truncated = false;
+    let (counts, output) = match output_path.filter(|p| !p.is_empty()) {
+        Some(dest) => {
+            let temp = match temp_file_beside(dest) {
+                Ok(t) => t,
+                Err(e) => return failed(format!("Cannot write file: {}", e)),
+            };
+            let mut writer = BufWriter::new(temp);
+            let counts = lua_filter_lines(path, script, |line| writer.write_all(line));
+            if counts.is_ok() {
+                let temp = match writer.into_inner() {
+                    Ok(t) => t,
+                    Err(_) => return failed("Cannot write file".to_string()),
+                };
+                if let Err(e) = temp.persist(dest) {
+                    return failed(format!("Cannot write file: {}", e.error));
+                }
             }
+            (counts, None)
         }
-    }
+        None => {
+            let mut matched = Vec::new();
+            let counts = lua_filter_lines(path, script, |line| {
+                if (matched.len() + line.len()) as u64 <= MAX_READ_SIZE {
+                    matched.extend_from_slice(line);
+                } else {
+                    truncated = true;
+                }
+                Ok(())
+            });
+            (counts, Some(String::from_utf8_lossy(&matched).into_owned()))
+        }
+    };
 
-    // Read existing recent files
-    let mut recent_files: Vec<RecentFile> = if recent_path.exists() {
-        fs::read_to_string(&recent_path)
-            .ok()
-            .and_then(|c| serde_json::from_str(&c).ok())
-            .unwrap_or_default()
-    } else {
-        vec![]
+    let (lines_matched, lines_processed) = match counts {
+        Ok(c) => c,
+        Err(e) => return failed(e),
     };
 
-    // Remove existing entry for this path (if any)
-    recent_files.retain(|f| f.path != path);
+    LuaFilterResult {
+        success: true,
+        lines_matched,
+        lines_processed,
+        output,
+        truncated,
+        execution_time_ms: started.elapsed().as_millis() as u64,
+        error: None,
+    }
+}
 
-    // Get file metadata
-    let metadata = fs::metadata(&path).ok();
-    let mtime = metadata.as_ref()
-        .and_then(|m| m.modified().ok())
-        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-        .map(|d| d.as_millis() as i64);
-    let size = metadata.as_ref().map(|m| m.len());
+/// A finished file session, appended to sessions_log.json
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionRecord {
+    pub path: String,
+    pub session_start: i64,
+    pub session_end: i64,
+    pub duration_secs: u64,
+}
 
-    // Create new entry
-    let new_entry = RecentFile {
-        path: path.clone(),
-        name: get_filename(&path),
-        last_opened: Utc::now().timestamp_millis(),
-        mtime,
-        size,
-        exists: metadata.is_some(),
-    };
+/// Result for end_file_session command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionDuration {
+    pub success: bool,
+    pub path: String,
+    pub duration_secs: u64,
+    pub session_start: i64,
+    pub session_end: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
 
-    // Prepend new entry
-    recent_files.insert(0, new_entry);
+/// Total recorded session time per path
+fn open_seconds_by_path(records: &[SessionRecord]) -> HashMap<String, u64> {
+    let mut totals = HashMap::new();
+    for r in records {
+        *totals.entry(r.path.clone()).or_insert(0u64) += r.duration_secs;
+    }
+    totals
+}
 
-    // Limit to MAX_RECENT entries
-    recent_files.truncate(MAX_RECENT);
+/// open_seconds_by_path for a sessions log, with the log's size and mtime when read
+pub struct SessionTotals {
+    log_path: PathBuf,
+    size: u64,
+    mtime: Option<i64>,
+    totals: HashMap<String, u64>,
+}
 
-    // Write back to file
-    let json = match serde_json::to_string_pretty(&recent_files) {
-        Ok(j) => j,
-        Err(_) => return false,
+/// Total recorded session time per path, parsing the log only when it has changed
+/// since the last call, so listing recent files page by page stays cheap
+fn session_totals(state: &AppState, log_path: &Path) -> HashMap<String, u64> {
+    let metadata = match fs::metadata(log_path) {
+        Ok(m) => m,
+        Err(_) => return HashMap::new(),
     };
+    let size = metadata.len();
+    let mtime = mtime_millis(&metadata);
 
-    let mut file = match OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open(&recent_path)
-    {
-        Ok(f) => f,
-        Err(_) => return false,
+    let mut cache = match state.session_totals.lock() {
+        Ok(c) => c,
+        Err(_) => return open_seconds_by_path(&load_json_list(log_path)),
     };
-
-    file.write_all(json.as_bytes()).is_ok()
+    if let Some(cached) = cache.as_ref().filter(|c| c.log_path == log_path && c.size == size && c.mtime == mtime) {
+        return cached.totals.clone();
+    }
+    let totals = open_seconds_by_path(&load_json_list(log_path));
+    *cache = Some(SessionTotals {
+        log_path: log_path.to_path_buf(),
+        size,
+        mtime,
+        totals: totals.clone(),
+    });
+    totals
 }
 
-/// Remove a single file from the recent files list
+/// Start timing how long a file is open; a session already running for it is kept
+/// Sessions are keyed by canonical path, matching the recent files list
 #[tauri::command]
-pub fn remove_recent_file(path: String) -> bool {
+pub fn start_file_session(state: State<'_, AppState>, path: String) -> bool {
     if path.is_empty() {
         return false;
     }
+    let path = normalize_path(path).canonical;
+    match state.open_sessions.lock() {
+        Ok(mut sessions) => {
+            sessions.entry(path).or_insert_with(|| Utc::now().timestamp_millis());
+            true
+        }
+        Err(_) => false,
+    }
+}
 
-    let recent_path = match get_recent_file_path() {
+/// End a file's session and record it in sessions_log.json
+#[tauri::command]
+pub fn end_file_session(state: State<'_, AppState>, path: String) -> SessionDuration {
+    let failed = |path: String, error: &str| SessionDuration {
+        success: false,
+        path,
+        duration_secs: 0,
+        session_start: 0,
+        session_end: 0,
+        error: Some(error.to_string()),
+    };
+
+    let path = normalize_path(path).canonical;
+    let session_start = match state.open_sessions.lock().map(|mut s| s.remove(&path)) {
+        Ok(Some(start)) => start,
+        Ok(None) => return failed(path, "No session started for this file"),
+        Err(_) => return failed(path, "Sessions unavailable"),
+    };
+    let session_end = Utc::now().timestamp_millis();
+    let duration_secs = (session_end - session_start).max(0) as u64 / 1000;
+
+    let log_path = match get_sessions_log_path() {
         Some(p) => p,
-        None => return false,
+        None => return failed(path, "Cannot determine home directory"),
     };
+    let mut records: Vec<SessionRecord> = load_json_list(&log_path);
+    records.push(SessionRecord {
+        path: path.clone(),
+        session_start,
+        session_end,
+        duration_secs,
+    });
+    if !save_json_list(&log_path, &records) {
+        return failed(path, "Cannot write sessions log");
+    }
 
-    if !recent_path.exists() {
-        return true; // Nothing to remove
+    SessionDuration {
+        success: true,
+        path,
+        duration_secs,
+        session_start,
+        session_end,
+        error: None,
     }
+}
 
-    // Read existing recent files
-    let mut recent_files: Vec<RecentFile> = match fs::read_to_string(&recent_path)
-        .ok()
-        .and_then(|c| serde_json::from_str(&c).ok())
-    {
-        Some(f) => f,
-        None => return false,
-    };
+/// Total seconds of recorded sessions for a file
+#[tauri::command]
+pub fn get_total_open_duration(state: State<'_, AppState>, path: String) -> u64 {
+    let path = normalize_path(path).canonical;
+    get_sessions_log_path()
+        .and_then(|p| session_totals(&state, &p).get(&path).copied())
+        .unwrap_or(0)
+}
 
-    // Remove the file from the list
-    recent_files.retain(|f| f.path != path);
+// Lines read from each end of a file while looking for timestamps to sample
+const CLOCK_SKEW_SCAN_LINES: usize = 10_000;
 
-    // Write back to file
-    let json = match serde_json::to_string_pretty(&recent_files) {
-        Ok(j) => j,
-        Err(_) => return false,
-    };
+/// Result for compare_log_timestamps command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClockSkewResult {
+    pub success: bool,
+    pub first_ts_a: Option<i64>,
+    pub first_ts_b: Option<i64>,
+    pub last_ts_a: Option<i64>,
+    pub last_ts_b: Option<i64>,
+    /// How far b's clock runs ahead of a's (negative when behind), from events
+    /// logged with the same message in both files
+    pub estimated_skew_ms: Option<i64>,
+    /// [max(first_a, first_b), min(last_a, last_b)] when the files overlap
+    pub common_window: Option<(i64, i64)>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
 
-    let mut file = match OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open(&recent_path)
-    {
-        Ok(f) => f,
-        Err(_) => return false,
-    };
+/// A line's timestamp and the message that follows it
+type TimestampedLine = (i64, String);
+
+/// Up to sample_size timestamped lines from the head of a file and from its tail,
+/// the tail ones newest first
+fn sample_timestamped_lines(
+    path: &str,
+    pattern: &str,
+    sample_size: usize,
+) -> std::io::Result<(Vec<TimestampedLine>, Vec<TimestampedLine>)> {
+    let mut head = Vec::new();
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut buf = Vec::new();
+    for _ in 0..CLOCK_SKEW_SCAN_LINES {
+        buf.clear();
+        if head.len() >= sample_size || reader.read_until(b'\n', &mut buf)? == 0 {
+            break;
+        }
+        let line = String::from_utf8_lossy(trim_line_ending(&buf));
+        if let Some((ts, rest)) = split_line_timestamp(&line, pattern) {
+            head.push((ts, rest.trim().to_string()));
+        }
+    }
 
-    file.write_all(json.as_bytes()).is_ok()
+    let mut tail = Vec::new();
+    let mut reverse = ReverseLineReader::open(path)?;
+    for _ in 0..CLOCK_SKEW_SCAN_LINES {
+        if tail.len() >= sample_size {
+            break;
+        }
+        let line = match reverse.next_line()? {
+            Some((_, bytes)) => bytes,
+            None => break,
+        };
+        let line = String::from_utf8_lossy(trim_line_ending(&line));
+        if let Some((ts, rest)) = split_line_timestamp(&line, pattern) {
+            tail.push((ts, rest.trim().to_string()));
+        }
+    }
+
+    Ok((head, tail))
 }
 
-/// Clear the recent files list
+/// Timestamp of each non-empty message, leaving out messages logged at more than one
+/// time since those can't be matched to a single event
+fn message_times<'a>(lines: impl Iterator<Item = &'a TimestampedLine>) -> HashMap<&'a str, i64> {
+    let mut times: HashMap<&str, Option<i64>> = HashMap::new();
+    for (ts, message) in lines.filter(|(_, m)| !m.is_empty()) {
+        let time = times.entry(message).or_insert(Some(*ts));
+        if *time != Some(*ts) {
+            *time = None;
+        }
+    }
+    times.into_iter().filter_map(|(m, ts)| Some((m, ts?))).collect()
+}
+
+/// Compare the time ranges of two log files and estimate the clock skew between them
+/// Samples sample_size timestamps from each file's head and tail; the skew is the
+/// median offset between lines with the same message in both files, if there are any
 #[tauri::command]
-pub fn clear_recent_files() -> bool {
-    let recent_path = match get_recent_file_path() {
-        Some(p) => p,
-        None => return false,
+pub fn compare_log_timestamps(
+    path_a: String,
+    path_b: String,
+    timestamp_pattern: String,
+    sample_size: usize,
+) -> ClockSkewResult {
+    let failed = |error: &str| ClockSkewResult {
+        success: false,
+        first_ts_a: None,
+        first_ts_b: None,
+        last_ts_a: None,
+        last_ts_b: None,
+        estimated_skew_ms: None,
+        common_window: None,
+        error: Some(error.to_string()),
     };
 
-    // Write empty array to file
-    let json = "[]";
+    if timestamp_pattern.is_empty() || sample_size == 0 {
+        return failed("Invalid parameters");
+    }
 
-    let mut file = match OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open(&recent_path)
-    {
-        Ok(f) => f,
-        Err(_) => return false,
+    let (head_a, tail_a) = match sample_timestamped_lines(&path_a, &timestamp_pattern, sample_size) {
+        Ok(s) => s,
+        Err(_) => return failed("Cannot read first file"),
+    };
+    let (head_b, tail_b) = match sample_timestamped_lines(&path_b, &timestamp_pattern, sample_size) {
+        Ok(s) => s,
+        Err(_) => return failed("Cannot read second file"),
     };
 
-    file.write_all(json.as_bytes()).is_ok()
-}
+    let first_ts_a = head_a.first().map(|(ts, _)| *ts);
+    let first_ts_b = head_b.first().map(|(ts, _)| *ts);
+    let last_ts_a = tail_a.first().map(|(ts, _)| *ts);
+    let last_ts_b = tail_b.first().map(|(ts, _)| *ts);
 
-/// Export content to a file (used for logbook export)
-#[tauri::command]
-pub fn export_file(path: String, content: String) -> bool {
-    if path.is_empty() {
-        return false;
+    let common_window = match (first_ts_a, first_ts_b, last_ts_a, last_ts_b) {
+        (Some(fa), Some(fb), Some(la), Some(lb)) if fa.max(fb) <= la.min(lb) => Some((fa.max(fb), la.min(lb))),
+        _ => None,
+    };
+
+    // Correlate events by message, pairing each message's time in a with its time in b
+    let times_a = message_times(head_a.iter().chain(&tail_a));
+    let times_b = message_times(head_b.iter().chain(&tail_b));
+    let mut offsets: Vec<i64> = times_b.iter()
+        .filter_map(|(message, ts_b)| Some(ts_b - times_a.get(message)?))
+        .collect();
+    offsets.sort_unstable();
+    let estimated_skew_ms = offsets.get(offsets.len() / 2).copied();
+
+    ClockSkewResult {
+        success: true,
+        first_ts_a,
+        first_ts_b,
+        last_ts_a,
+        last_ts_b,
+        estimated_skew_ms,
+        common_window,
+        error: None,
     }
+}
+
+// Non-empty lines from the start of a file tested by get_log_format_hints
+const FORMAT_HINT_SAMPLE_LINES: usize = 100;
 
-    fs::write(&path, content.as_bytes()).is_ok()
+// A date and time (or just a time) at the start of a line, optionally bracketed, e.g.
+// 2024-01-15 10:30:00, [2024-01-15T10:30:00Z] or 10:30:00.123
+const LEADING_TIMESTAMP_PATTERN: &str = r"^\[?(?:\d{4}[-/]\d{2}[-/]\d{2}[T ])?\d{2}:\d{2}:\d{2}";
+
+/// How well sampled lines fit one log format
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatConfidence {
+    pub format: String,
+    /// Fraction of sampled lines that fit the format, 0.0 to 1.0
+    pub confidence: f64,
+    pub evidence: String,
 }
 
-/// Result for search_file_for_line command
+/// Result for get_log_format_hints command
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
-pub struct SearchLineResult {
+pub struct FormatHints {
     pub success: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub content: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub line_number: Option<usize>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub total_lines: Option<usize>,
+    /// Most likely format first; formats no sampled line fits are left out
+    pub detected_formats: Vec<FormatConfidence>,
+    pub sample_lines: Vec<String>,
+    pub line_count_sampled: usize,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
 }
 
-/// Search for a specific line in a file and return surrounding context
-/// Used for "jump to source" when the log is outside the truncated view
+/// Guess a log's format from its first non-empty lines, to pick a parser such as
+/// parse_json_log, parse_apache_access_log, parse_nginx_access_log, read_syslog or parse_csv_log
 #[tauri::command]
-pub fn search_file_for_line(path: String, search_line: String, context_lines: usize) -> SearchLineResult {
-    if path.is_empty() || search_line.is_empty() {
-        return SearchLineResult {
-            success: false,
-            content: None,
-            line_number: None,
-            total_lines: None,
-            error: Some("Invalid parameters".to_string()),
-        };
-    }
+pub fn get_log_format_hints(path: String) -> FormatHints {
+    let failed = |error: &str| FormatHints {
+        success: false,
+        detected_formats: vec![],
+        sample_lines: vec![],
+        line_count_sampled: 0,
+        error: Some(error.to_string()),
+    };
 
-    // Read the entire file
-    let content = match fs::read_to_string(&path) {
-        Ok(c) => c,
-        Err(_) => {
-            return SearchLineResult {
-                success: false,
-                content: None,
-                line_number: None,
-                total_lines: None,
-                error: Some("Cannot read file".to_string()),
-            };
-        }
+    let file = match File::open(&path) {
+        Ok(f) => f,
+        Err(_) => return failed("Cannot open file"),
     };
 
-    let lines: Vec<&str> = content.lines().collect();
-    let total_lines = lines.len();
+    let mut reader = BufReader::new(file);
+    let mut sample_lines = Vec::new();
+    let mut buf = Vec::new();
+    while sample_lines.len() < FORMAT_HINT_SAMPLE_LINES {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(_) => return failed("Cannot read file"),
+        }
+        let line = String::from_utf8_lossy(trim_line_ending(&buf));
+        if !line.trim().is_empty() {
+            sample_lines.push(line.into_owned());
+        }
+    }
 
-    // Search for the exact line
-    let found_index = lines.iter().position(|&line| line == search_line);
+    // More specific formats come first so they win ties, e.g. Nginx combined over Apache CLF
+    let patterns = [
+        ("nginx_combined", NGINX_COMBINED_PATTERN, "match the Nginx combined format"),
+        ("apache_clf", APACHE_CLF_PATTERN, "match the Apache Common Log Format"),
+        ("syslog_rfc3164", SYSLOG_RFC3164_PATTERN, "match the RFC 3164 syslog format"),
+        ("timestamped_text", LEADING_TIMESTAMP_PATTERN, "start with a timestamp"),
+    ];
+    let mut counts: Vec<(&str, usize, &str)> = Vec::new();
 
-    match found_index {
-        Some(idx) => {
-            // Calculate context window
-            let start = if idx > context_lines { idx - context_lines } else { 0 };
-            let end = std::cmp::min(idx + context_lines + 1, total_lines);
+    let json_count = sample_lines.iter()
+        .filter(|l| serde_json::from_str::<serde_json::Value>(l).is_ok_and(|v| v.is_object()))
+        .count();
+    counts.push(("json", json_count, "parse as JSON objects"));
 
-            // Extract lines with context
-            let context_content: String = lines[start..end].join("\n");
+    for (format, pattern, evidence) in patterns {
+        let re = match Regex::new(pattern) {
+            Ok(re) => re,
+            Err(_) => continue,
+        };
+        let matched = sample_lines.iter().filter(|l| re.is_match(l)).count();
+        counts.push((format, matched, evidence));
+    }
 
-            SearchLineResult {
-                success: true,
-                content: Some(context_content),
-                line_number: Some(idx + 1), // 1-indexed
-                total_lines: Some(total_lines),
-                error: None,
-            }
-        }
-        None => {
-            SearchLineResult {
-                success: false,
-                content: None,
-                line_number: None,
-                total_lines: Some(total_lines),
-                error: Some("Line not found in file".to_string()),
+    // CSV lines split into the same number (at least two) of fields; count the most common one
+    let mut field_counts: HashMap<usize, usize> = HashMap::new();
+    for line in &sample_lines {
+        let mut csv_reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(line.as_bytes());
+        if let Some(Ok(record)) = csv_reader.records().next() {
+            if record.len() >= 2 {
+                *field_counts.entry(record.len()).or_insert(0) += 1;
             }
         }
     }
+    let csv_count = field_counts.values().copied().max().unwrap_or(0);
+    counts.push(("csv", csv_count, "have the same number of comma-separated fields"));
+
+    let line_count_sampled = sample_lines.len();
+    let mut detected_formats: Vec<FormatConfidence> = counts.into_iter()
+        .filter(|(_, count, _)| *count > 0)
+        .map(|(format, count, evidence)| FormatConfidence {
+            format: format.to_string(),
+            confidence: count as f64 / line_count_sampled as f64,
+            evidence: format!("{} of {} sampled lines {}", count, line_count_sampled, evidence),
+        })
+        .collect();
+    detected_formats.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+
+    FormatHints {
+        success: true,
+        detected_formats,
+        sample_lines,
+        line_count_sampled,
+        error: None,
+    }
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,864 @@
+use super::*;
+use std::sync::MutexGuard;
+use tauri::Manager;
+
+// MOCHA_CONFIG_DIR is process-wide, so tests that set it take turns
+static CONFIG_DIR_LOCK: Mutex<()> = Mutex::new(());
+
+/// Points MOCHA_CONFIG_DIR at a fresh temporary directory until dropped
+struct TempConfigDir {
+    dir: tempfile::TempDir,
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl TempConfigDir {
+    fn new() -> Self {
+        let guard = CONFIG_DIR_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = tempfile::tempdir().unwrap();
+        std::env::set_var("MOCHA_CONFIG_DIR", dir.path());
+        TempConfigDir { dir, _guard: guard }
+    }
+
+    fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Create a file with content inside the config directory, returning its canonical path
+    fn file(&self, name: &str, content: &str) -> String {
+        let path = self.path().join(name);
+        fs::write(&path, content).unwrap();
+        fs::canonicalize(path).unwrap().to_string_lossy().into_owned()
+    }
+
+    /// recent.json as raw JSON
+    fn recent_json(&self) -> Vec<serde_json::Value> {
+        let content = fs::read_to_string(self.path().join("recent.json")).unwrap();
+        serde_json::from_str(&content).unwrap()
+    }
+}
+
+impl Drop for TempConfigDir {
+    fn drop(&mut self) {
+        std::env::remove_var("MOCHA_CONFIG_DIR");
+    }
+}
+
+/// Run f with the State of a mock app managing a fresh AppState
+fn with_app_state<T>(f: impl FnOnce(State<'_, AppState>) -> T) -> T {
+    let app = tauri::test::mock_app();
+    app.manage(AppState::new());
+    f(app.state::<AppState>())
+}
+
+#[test]
+fn alias_is_stored_apart_from_name() {
+    let config = TempConfigDir::new();
+    let path = config.file("app.log", "hello\n");
+
+    with_app_state(|state| {
+        assert!(add_recent_file(state.clone(), path.clone()));
+        assert!(rename_recent_file(state, path.clone(), "Server".to_string()));
+    });
+
+    let stored = config.recent_json();
+    assert_eq!(stored[0]["name"], "app.log");
+    assert_eq!(stored[0]["alias"], "Server");
+
+    let loaded = load_recent_files(&config.path().join("recent.json")).unwrap();
+    assert_eq!(loaded[0].name, "app.log");
+    let sent = serde_json::to_value(&loaded[0]).unwrap();
+    assert_eq!(sent["name"], "Server");
+    assert_eq!(sent["alias"], "Server");
+}
+
+#[test]
+fn export_as_html_numbers_the_last_lines() {
+    let config = TempConfigDir::new();
+    let lines: Vec<String> = (1..=3000).map(|i| format!("line {} <{}>", i, i)).collect();
+    let source = config.file("app.log", &(lines.join("\n") + "\n"));
+    let dest = config.path().join("app.html");
+
+    let result = with_app_state(|state| {
+        export_as_html(state, source.clone(), dest.to_string_lossy().into_owned(), Some("line 3000".to_string()), Some(2))
+    });
+    assert!(result.success);
+
+    let html = fs::read_to_string(&dest).unwrap();
+    assert_eq!(result.bytes_written, html.len() as u64);
+    assert!(!html.contains("line 2998"));
+    assert!(html.contains("<span class=\"ln\">2999</span>line 2999 &lt;2999&gt;"));
+    assert!(html.contains("<span class=\"ln\">3000</span><span class=\"highlight\">line 3000 &lt;3000&gt;</span>"));
+    assert!(html.ends_with(HTML_EXPORT_END));
+}
+
+#[test]
+fn export_as_html_streams_whole_file() {
+    let config = TempConfigDir::new();
+    let source = config.file("app.log", "first\r\nsecond");
+    let dest = config.path().join("app.html");
+
+    let result = with_app_state(|state| {
+        export_as_html(state, source.clone(), dest.to_string_lossy().into_owned(), None, None)
+    });
+    assert!(result.success);
+
+    let html = fs::read_to_string(&dest).unwrap();
+    assert!(html.contains("<span class=\"ln\">1</span>first</span><span class=\"line\"><span class=\"ln\">2</span>second</span></pre>"));
+}
+
+#[test]
+fn skip_partial_first_line_lf() {
+    assert_eq!(skip_partial_first_line("tial\nnext\nlast\n"), Some("next\nlast\n"));
+    assert_eq!(skip_partial_first_line("\nnext\n"), Some("next\n"));
+    assert_eq!(skip_partial_first_line("no newline"), None);
+}
+
+#[test]
+fn skip_partial_first_line_crlf() {
+    assert_eq!(skip_partial_first_line("tial\r\nnext\r\nlast\r\n"), Some("next\r\nlast\r\n"));
+    // Starting between the \r and \n of the cut line
+    assert_eq!(skip_partial_first_line("\nnext\r\n"), Some("next\r\n"));
+}
+
+#[test]
+fn skip_partial_first_line_mixed() {
+    // A line that itself starts with \r keeps it; it isn't part of the previous ending
+    assert_eq!(skip_partial_first_line("tial\n\rnext\r\nlast\n"), Some("\rnext\r\nlast\n"));
+    assert_eq!(skip_partial_first_line("tial\r\n\nlast\n"), Some("\nlast\n"));
+    assert_eq!(skip_partial_first_line("tial\n\r\nlast\n"), Some("\r\nlast\n"));
+}
+
+#[test]
+fn read_range_lossy_stops_at_current_end() {
+    let config = TempConfigDir::new();
+    let path = config.file("app.log", &"x".repeat(2 * 1024 * 1024));
+
+    // As if the file had been truncated after a larger size was stat'ed
+    let content = read_range_lossy(&path, 1024 * 1024, 4 * 1024 * 1024).unwrap();
+    assert_eq!(content.len(), 1024 * 1024);
+    assert_eq!(read_range_lossy(&path, 3 * 1024 * 1024, 2 * 1024 * 1024).unwrap(), "");
+}
+
+/// Run a future to completion on a single-threaded tokio runtime
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+#[test]
+fn read_file_async_matches_sync() {
+    let config = TempConfigDir::new();
+    let lines: Vec<String> = (0..200_000).map(|i| format!("[INFO] line {}", i)).collect();
+    let path = config.file("app.log", &(lines.join("\r\n") + "\r\n"));
+    let state = AppState::new();
+
+    let mut sync_progress = Vec::new();
+    let mut async_progress = Vec::new();
+    let size = fs::metadata(&path).unwrap().len();
+    for offset in [0, 1000, 50_000, size] {
+        let sync = read_file_with_progress(&state, path.clone(), offset, Some(r"^\[\w+\] ".to_string()), true, None, |p| {
+            sync_progress.push(p.bytes_read)
+        });
+        let async_result = block_on(read_file_tokio(&state, path.clone(), offset, Some(r"^\[\w+\] ".to_string()), true, None, |p| {
+            async_progress.push(p.bytes_read)
+        }));
+
+        assert!(sync.success);
+        assert_eq!(serde_json::to_value(&sync).unwrap(), serde_json::to_value(&async_result).unwrap());
+    }
+    // Memory-mapped sync reads finish in one step without progress events
+    #[cfg(not(feature = "mmap-reads"))]
+    {
+        assert!(!sync_progress.is_empty());
+        assert_eq!(sync_progress, async_progress);
+    }
+
+    let missing = config.path().join("missing.log").to_string_lossy().into_owned();
+    let sync = read_file_with_progress(&state, missing.clone(), 0, None, false, None, |_| {});
+    let async_result = block_on(read_file_tokio(&state, missing, 0, None, false, None, |_| {}));
+    assert_eq!(sync.error, async_result.error);
+}
+
+#[test]
+fn file_deleted_after_stat_is_reported() {
+    let config = TempConfigDir::new();
+    let path = config.file("app.log", "hello\n");
+
+    let delete_after_stat = || {
+        BETWEEN_STAT_AND_OPEN.with(|h| *h.borrow_mut() = Some(Box::new(|p: &str| fs::remove_file(p).unwrap())));
+    };
+    let (result, async_result) = with_app_state(|state| {
+        delete_after_stat();
+        let result = read_file_with_progress(&state, path.clone(), 0, None, false, None, |_| {});
+        fs::write(&path, "hello\n").unwrap();
+        delete_after_stat();
+        let async_result = block_on(read_file_tokio(&state, path.clone(), 0, None, false, None, |_| {}));
+        (result, async_result)
+    });
+
+    assert_eq!(async_result.error_code, Some(CommandError::FileDisappearedBetweenStatAndOpen));
+    assert!(!result.success);
+    assert_eq!(result.error_code, Some(CommandError::FileDisappearedBetweenStatAndOpen));
+    assert_eq!(result.error.as_deref(), Some(CommandError::FileDisappearedBetweenStatAndOpen.message()));
+    let sent = serde_json::to_value(&result).unwrap();
+    assert_eq!(sent["error_code"], "FileDisappearedBetweenStatAndOpen");
+
+    let denied = open_after_stat_error(std::io::Error::from(std::io::ErrorKind::PermissionDenied));
+    assert_eq!(denied.error_code, None);
+    assert_eq!(denied.error.as_deref(), Some("Cannot open file"));
+}
+
+#[test]
+fn get_filename_handles_paths_without_a_name() {
+    assert_eq!(get_filename("/var/log/app.log"), "app.log");
+    assert_eq!(get_filename("app.log"), "app.log");
+    assert_eq!(get_filename("/"), "/");
+    assert_eq!(get_filename(".."), "..");
+}
+
+#[test]
+fn display_name_relative_to_base() {
+    assert_eq!(display_name_relative_to("/var/log/app/server.log", "/var/log"), "app/server.log");
+    assert_eq!(display_name_relative_to("/var/log/app/server.log", "/var/log/"), "app/server.log");
+    assert_eq!(display_name_relative_to("/var/log/app/server.log", "/srv"), "server.log");
+    assert_eq!(display_name_relative_to("/var/log", "/var/log/"), "log");
+    // Only whole components match, so /var/logs is not inside /var/log
+    assert_eq!(display_name_relative_to("/var/logs/server.log", "/var/log"), "server.log");
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn parallel_metadata_refresh_keeps_order() {
+    let config = TempConfigDir::new();
+    let paths: Vec<String> = (0..20).map(|i| config.file(&format!("{}.log", i), &"x".repeat(i))).collect();
+    let entries: Vec<RecentFile> = paths.iter()
+        .map(|p| serde_json::from_value(serde_json::json!({ "path": p, "name": get_filename(p), "lastOpened": 0 })).unwrap())
+        .collect();
+
+    // Slow stats that take longer for earlier entries, so lookups finish out of order
+    let slow_stat = |path: &str| {
+        let metadata = fs::metadata(path).ok();
+        let size = metadata.as_ref().map_or(0, |m| m.len());
+        std::thread::sleep(std::time::Duration::from_millis(20 - size));
+        metadata
+    };
+
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(8).build().unwrap();
+    let mut parallel = entries;
+    pool.install(|| refresh_recent_files_with(&mut parallel, slow_stat));
+
+    let order: Vec<&str> = parallel.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(order, paths);
+    for (i, f) in parallel.iter().enumerate() {
+        assert_eq!(f.size, Some(i as u64));
+        assert!(f.exists && f.metadata_fresh);
+    }
+}
+
+#[test]
+fn re_adding_a_file_keeps_its_settings() {
+    let config = TempConfigDir::new();
+    let path = config.file("app.log", "hello\n");
+    let other = config.file("other.log", "hello\n");
+
+    with_app_state(|state| {
+        assert!(add_recent_file(state.clone(), path.clone()));
+        assert!(rename_recent_file(state.clone(), path.clone(), "Server".to_string()));
+        assert!(tag_recent_file(state.clone(), path.clone(), vec!["prod".to_string()]));
+        assert!(set_recent_file_pinned(state.clone(), path.clone(), true));
+        assert!(add_recent_file(state.clone(), other));
+        assert!(add_recent_file(state, path.clone()));
+    });
+
+    let stored = config.recent_json();
+    assert_eq!(stored[0]["path"], path.as_str());
+    assert_eq!(stored[0]["alias"], "Server");
+    assert_eq!(stored[0]["tags"], serde_json::json!(["prod"]));
+    assert_eq!(stored[0]["isPinned"], true);
+}
+
+#[test]
+fn adding_the_same_path_counts_opens() {
+    let config = TempConfigDir::new();
+    let path = config.file("app.log", "hello\n");
+
+    with_app_state(|state| {
+        for _ in 0..3 {
+            assert!(add_recent_file(state.clone(), path.clone()));
+        }
+    });
+
+    let stored = config.recent_json();
+    assert_eq!(stored.len(), 1);
+    assert_eq!(stored[0]["openCount"], 3);
+}
+
+#[test]
+fn search_offsets_line_up_with_read_file() {
+    let config = TempConfigDir::new();
+    let lines: Vec<String> = (0..1000).map(|i| format!("é event {}", i)).collect();
+    let path = config.file("app.log", &(lines.join("\r\n") + "\r\n"));
+
+    with_app_state(|state| {
+        let found = search_file_for_line(state.clone(), path.clone(), "é event 700".to_string(), 2, false, None);
+        assert!(found.success);
+        assert_eq!(found.line_number, Some(701));
+
+        let at_match = read_file_with_progress(&state, path.clone(), found.byte_offset.unwrap(), None, false, None, |_| {});
+        assert!(at_match.content.unwrap().starts_with("é event 700\r\n"));
+
+        let at_context = read_file_with_progress(&state, path.clone(), found.context_start_offset.unwrap(), None, false, None, |_| {});
+        assert!(at_context.content.unwrap().starts_with("é event 698\r\n"));
+    });
+}
+
+#[test]
+fn export_file_creates_missing_parents() {
+    let config = TempConfigDir::new();
+    let dest = config.path().join("a").join("b").join("c").join("logbook.md");
+
+    let result = export_file(dest.to_string_lossy().into_owned(), "# Notes\n".to_string(), None, false, None);
+
+    assert!(result.success);
+    assert!(result.created_dirs);
+    assert_eq!(fs::read_to_string(&dest).unwrap(), "# Notes\n");
+}
+
+#[test]
+fn export_recent_files_creates_missing_parents() {
+    let config = TempConfigDir::new();
+    let path = config.file("app.log", "hello\n");
+    with_app_state(|state| assert!(add_recent_file(state, path.clone())));
+    let dest = config.path().join("a").join("b").join("c").join("recent.json");
+
+    assert!(export_recent_files(dest.to_string_lossy().into_owned()));
+
+    let exported: serde_json::Value = serde_json::from_str(&fs::read_to_string(&dest).unwrap()).unwrap();
+    assert_eq!(exported["files"][0]["path"], path.as_str());
+}
+
+/// Counts the bytes each thread has allocated, so a test can measure its own peak
+/// memory use while other tests run alongside it
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATED: std::cell::Cell<isize> = const { std::cell::Cell::new(0) };
+    static PEAK_ALLOCATED: std::cell::Cell<isize> = const { std::cell::Cell::new(0) };
+}
+
+unsafe impl std::alloc::GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+        let _ = ALLOCATED.try_with(|a| {
+            a.set(a.get() + layout.size() as isize);
+            let _ = PEAK_ALLOCATED.try_with(|p| p.set(p.get().max(a.get())));
+        });
+        std::alloc::System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+        let _ = ALLOCATED.try_with(|a| a.set(a.get() - layout.size() as isize));
+        std::alloc::System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Run f and return its result with the most memory it had allocated at once
+fn peak_allocation<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let base = ALLOCATED.with(|a| a.get());
+    PEAK_ALLOCATED.with(|p| p.set(base));
+    let result = f();
+    let peak = PEAK_ALLOCATED.with(|p| p.get()) - base;
+    (result, peak.max(0) as usize)
+}
+
+#[test]
+fn search_file_for_line_memory_is_bounded() {
+    let config = TempConfigDir::new();
+    let line = format!("{}\n", "x".repeat(99));
+    let mut content = line.repeat(200_000);
+    content.push_str("needle\n");
+    let path = config.file("big.log", &content);
+    drop(content);
+
+    // What loading the whole file, as the search used to, costs
+    let (whole, whole_peak) = peak_allocation(|| fs::read_to_string(&path).unwrap());
+    drop(whole);
+
+    let (found, search_peak) = with_app_state(|state| {
+        peak_allocation(|| search_file_for_line(state, path.clone(), "needle".to_string(), 3, false, None))
+    });
+
+    assert!(found.success);
+    assert_eq!(found.line_number, Some(200_001));
+    assert!(whole_peak >= 20_000_000);
+    assert!(
+        search_peak * 20 < whole_peak,
+        "search peaked at {} bytes, reading the file at {}",
+        search_peak,
+        whole_peak
+    );
+}
+
+#[test]
+fn config_paths_follow_mocha_config_dir() {
+    let config = TempConfigDir::new();
+
+    let paths = [
+        get_config_dir(),
+        get_recent_file_path(),
+        get_recent_backup_path(),
+        get_saved_searches_path(),
+        get_bookmarks_path(),
+        get_annotations_path(),
+        get_config_lock_path(),
+        get_sessions_log_path(),
+        get_sessions_dir(),
+        get_backups_dir(),
+        get_search_history_path(),
+        get_config_path(),
+    ];
+    for path in paths {
+        assert!(path.unwrap().starts_with(config.path()));
+    }
+}
+
+#[test]
+fn sessions_are_keyed_by_canonical_path() {
+    let config = TempConfigDir::new();
+    let path = config.file("app.log", "hello\n");
+    let spelled_differently = config.path().join(".").join("app.log").to_string_lossy().into_owned();
+
+    with_app_state(|state| {
+        assert!(start_file_session(state.clone(), spelled_differently));
+        let ended = end_file_session(state, path.clone());
+        assert!(ended.success);
+        assert_eq!(ended.path, path);
+    });
+
+    let records = load_json_list::<SessionRecord>(&config.path().join("sessions_log.json"));
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].path, path);
+}
+
+#[test]
+fn session_totals_follow_log_changes() {
+    let config = TempConfigDir::new();
+    let log_path = config.path().join("sessions_log.json");
+    let record = |path: &str, duration_secs| SessionRecord {
+        path: path.to_string(),
+        session_start: 0,
+        session_end: 0,
+        duration_secs,
+    };
+
+    with_app_state(|state| {
+        let mut records = vec![record("/a.log", 30), record("/b.log", 5)];
+        assert!(save_json_list(&log_path, &records));
+        assert_eq!(session_totals(&state, &log_path).get("/a.log"), Some(&30));
+
+        records.push(record("/a.log", 12));
+        assert!(save_json_list(&log_path, &records));
+        assert_eq!(session_totals(&state, &log_path).get("/a.log"), Some(&42));
+        assert_eq!(get_total_open_duration(state, "/a.log".to_string()), 42);
+    });
+
+    // Only the log itself is left behind by the atomic writes
+    let entries: Vec<_> = fs::read_dir(config.path()).unwrap().collect();
+    assert_eq!(entries.len(), 1);
+}
+
+#[test]
+fn derived_fields_are_not_persisted() {
+    let config = TempConfigDir::new();
+    let path = config.file("app.log", "hello\n");
+    with_app_state(|state| assert!(add_recent_file(state, path.clone())));
+
+    let stored = config.recent_json();
+    assert!(stored[0]["mtime"].is_i64());
+    assert!(stored[0].get("mtimeIso").is_none());
+    assert!(stored[0].get("totalOpenSeconds").is_none());
+
+    let mut loaded = load_recent_files(&config.path().join("recent.json")).unwrap();
+    loaded[0].total_open_seconds = Some(5);
+    let sent = serde_json::to_value(&loaded[0]).unwrap();
+    assert_eq!(sent["mtimeIso"], mtime_iso(loaded[0].mtime.unwrap()).unwrap());
+    assert_eq!(sent["totalOpenSeconds"], 5);
+}
+
+#[test]
+fn diff_files_reports_file_too_large() {
+    let config = TempConfigDir::new();
+    let small = config.file("small.log", "a\n");
+    let big = config.file("big.log", "");
+    File::options().write(true).open(&big).unwrap().set_len(MAX_DIFF_FILE_SIZE + 1).unwrap();
+
+    let result = diff_files(small.clone(), big, "unified".to_string());
+    assert!(!result.success);
+    assert_eq!(result.error_code, Some(CommandError::FileTooLarge));
+    assert_eq!(serde_json::to_value(&result).unwrap()["errorCode"], "FileTooLarge");
+
+    let missing = diff_files(small, config.path().join("missing.log").to_string_lossy().into_owned(), "unified".to_string());
+    assert!(!missing.success);
+    assert_eq!(missing.error_code, None);
+}
+
+#[test]
+fn recent_file_notes_are_found_in_place() {
+    let config = TempConfigDir::new();
+    let first = config.file("first.log", "hello\n");
+    let second = config.file("second.log", "hello\n");
+
+    with_app_state(|state| {
+        assert!(add_recent_file(state.clone(), first.clone()));
+        assert!(add_recent_file(state.clone(), second.clone()));
+        assert!(set_recent_file_notes(state, second.clone(), "  auth service, incident #2341 ".to_string()));
+    });
+
+    // second is the newest entry, so entries after the match are skipped over
+    assert_eq!(get_recent_file_notes(second).as_deref(), Some("auth service, incident #2341"));
+    assert_eq!(get_recent_file_notes(first), None);
+    assert_eq!(get_recent_file_notes("/not/in/the/list.log".to_string()), None);
+}
+
+#[cfg(all(unix, feature = "unix-pipes"))]
+#[test]
+fn read_pipe_bytes_drains_a_busy_pipe() {
+    let config = TempConfigDir::new();
+    let fifo = config.path().join("stream.fifo");
+    let fifo_c = std::ffi::CString::new(fifo.to_string_lossy().into_owned()).unwrap();
+    assert_eq!(unsafe { libc::mkfifo(fifo_c.as_ptr(), 0o600) }, 0);
+
+    let payload: Vec<u8> = (0..1024 * 1024).map(|i| b'a' + (i % 26) as u8).collect();
+    let writer = {
+        let fifo = fifo.clone();
+        let payload = payload.clone();
+        std::thread::spawn(move || {
+            let mut pipe = OpenOptions::new().write(true).open(fifo).unwrap();
+            pipe.write_all(&payload).unwrap();
+        })
+    };
+
+    let data = read_pipe_bytes(&fifo.to_string_lossy(), 300, 4 * 1024 * 1024).unwrap();
+    writer.join().unwrap();
+    assert_eq!(data, payload);
+}
+
+#[test]
+fn read_log_file_bytes_reads_every_gzip_member() {
+    use flate2::write::GzEncoder;
+
+    let config = TempConfigDir::new();
+    let path = config.path().join("app.log.1.gz");
+    let mut bytes = Vec::new();
+    for part in ["first\n", "second\n"] {
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(part.as_bytes()).unwrap();
+        bytes.extend(encoder.finish().unwrap());
+    }
+    fs::write(&path, bytes).unwrap();
+
+    assert_eq!(read_log_file_bytes(&path, 1024).unwrap(), b"first\nsecond\n");
+    assert_eq!(read_log_file_bytes(&path, 8).unwrap(), b"first\nse");
+}
+
+#[test]
+fn estimate_line_count_handles_huge_sample_sizes() {
+    let config = TempConfigDir::new();
+    let path = config.file("app.log", &"line\n".repeat(300_000));
+
+    let result = estimate_line_count(path.clone(), Some(u64::MAX));
+    assert!(result.success);
+    assert!(result.exact);
+    assert_eq!(result.estimated_lines, 300_000);
+
+    let sampled = estimate_line_count(path, Some(4));
+    assert!(!sampled.exact);
+    assert!(sampled.estimated_lines.abs_diff(300_000) < 3_000);
+}
+
+#[test]
+fn json_lists_round_trip() {
+    let config = TempConfigDir::new();
+    let path = config.path().join("nested").join("bookmarks.json");
+
+    assert!(load_json_list::<SessionRecord>(&path).is_empty());
+    let records = vec![SessionRecord {
+        path: "/a.log".to_string(),
+        session_start: 1,
+        session_end: 2,
+        duration_secs: 3,
+    }];
+    assert!(save_json_list(&path, &records));
+
+    let loaded: Vec<SessionRecord> = load_json_list(&path);
+    assert_eq!(loaded.len(), 1);
+    assert_eq!(loaded[0].path, "/a.log");
+    assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+}
+
+#[test]
+fn top_messages_counts_unique_messages() {
+    let config = TempConfigDir::new();
+    let path = config.file("app.log", "retry 1\nretry 2\nstarted\nretry 3\n\nstarted\n");
+
+    let result = get_top_messages(path.clone(), 1, true);
+    assert!(result.success);
+    assert!(!result.approximate);
+    assert_eq!(result.total_unique, 2);
+    assert_eq!(result.entries[0].message, "retry #");
+    assert_eq!(result.entries[0].count, 3);
+    assert_eq!((result.entries[0].first_seen_line, result.entries[0].last_seen_line), (1, 4));
+
+    assert_eq!(get_top_messages(path, 1, false).total_unique, 4);
+}
+
+#[test]
+fn search_file_for_line_stops_past_line_range() {
+    let config = TempConfigDir::new();
+    let mut content = String::new();
+    for i in 0..1000 {
+        content.push_str(&format!("line {}\n", i));
+    }
+    content.push_str("needle\n");
+    let path = config.file("range.log", &content);
+
+    let (outside, inside) = with_app_state(|state| {
+        let outside = search_file_for_line(state.clone(), path.clone(), "needle".to_string(), 0, true, Some((1, 10)));
+        let inside = search_file_for_line(state, path.clone(), "line 5".to_string(), 2, true, Some((1, 10)));
+        (outside, inside)
+    });
+
+    assert!(!outside.success);
+    assert_eq!(outside.total_lines, None);
+    assert!(outside.bytes_scanned.unwrap() < content.len() as u64 / 10);
+
+    assert!(inside.success);
+    assert_eq!(inside.line_number, Some(6));
+    assert_eq!(inside.content.as_deref(), Some("line 3\nline 4\nline 5\nline 6\nline 7"));
+    assert_eq!(inside.total_lines, None);
+    assert!(inside.bytes_scanned.unwrap() < content.len() as u64 / 10);
+}
+
+#[test]
+fn corrupted_recent_json_is_restored_from_backup() {
+    let config = TempConfigDir::new();
+    let first = config.file("first.log", "a\n");
+    let second = config.file("second.log", "b\n");
+
+    let result = with_app_state(|state| {
+        assert!(add_recent_file(state.clone(), first.clone()));
+        // The second add backs up the one-entry list
+        assert!(add_recent_file(state.clone(), second.clone()));
+        fs::write(config.path().join("recent.json"), "[{\"path\": ").unwrap();
+        validate_recent_json(state)
+    });
+
+    assert!(!result.valid);
+    assert!(result.recovered);
+    assert_eq!(result.entry_count, Some(1));
+    assert_eq!(config.recent_json()[0]["path"], first.as_str());
+    // Only recent.json and its backup; no temp files left behind
+    let names: Vec<String> = fs::read_dir(config.path()).unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .filter(|n| n.starts_with("recent"))
+        .collect();
+    assert_eq!(names.len(), 2, "{:?}", names);
+}
+
+#[cfg(unix)]
+#[test]
+fn watch_target_matches_the_paths_notify_reports() {
+    let dir = tempfile::tempdir().unwrap();
+    let real = dir.path().join("real");
+    fs::create_dir(&real).unwrap();
+    fs::write(real.join("app.log"), "a\n").unwrap();
+    let link = dir.path().join("link");
+    std::os::unix::fs::symlink(&real, &link).unwrap();
+    let real = fs::canonicalize(&real).unwrap();
+
+    let existing = link.join("app.log");
+    assert_eq!(resolve_watch_target(&existing.to_string_lossy()), Some(real.join("app.log")));
+    // Not created yet: resolved through its directory
+    let missing = link.join("next.log");
+    assert_eq!(resolve_watch_target(&missing.to_string_lossy()), Some(real.join("next.log")));
+    assert_eq!(resolve_watch_target(&link.join("nope/app.log").to_string_lossy()), None);
+}
+
+#[test]
+fn log_level_counts_match_a_line_by_line_count() {
+    let config = TempConfigDir::new();
+    let levels = ["error", "Warning", "INFO", "debug", "plain", "TRACE"];
+    let mut content = String::new();
+    // Long enough that lines straddle the read chunks
+    for i in 0..40_000 {
+        content.push_str(&format!("{} {} request {}\r\n", i, levels[i % levels.len()], "x".repeat(i % 13)));
+    }
+    content.push_str("last ERROR without newline");
+    let path = config.file("levels.log", &content);
+
+    let counts = get_log_level_counts(path.clone(), None);
+    assert!(counts.success);
+    assert_eq!(counts.total_lines, 40_001);
+    let expected = |needle: &str| content.lines().filter(|l| l.to_lowercase().contains(needle)).count() as u64;
+    for label in DEFAULT_LEVEL_LABELS {
+        assert_eq!(counts.counts[label], expected(&label.to_lowercase()), "{}", label);
+    }
+    assert_eq!(counts.unmatched_lines, 40_000 / 6);
+
+    let patterns = HashMap::from([
+        ("bad".to_string(), "^\\d+ (error|ERROR)".to_string()),
+        ("long".to_string(), "x{12}$".to_string()),
+    ]);
+    let custom = get_log_level_counts(path, Some(patterns));
+    assert!(custom.success);
+    assert_eq!(custom.counts["bad"], 40_000 / 6 + 1);
+    assert_eq!(custom.counts["long"], content.lines().filter(|l| l.ends_with(&"x".repeat(12))).count() as u64);
+}
+
+#[test]
+fn count_pattern_occurrences_across_chunk_boundaries() {
+    let config = TempConfigDir::new();
+    // "needle" straddles the first chunk boundary
+    let mut content = "x".repeat(COUNT_BUFFER_SIZE - 3);
+    content.push_str("needle\n");
+    for i in 0..50_000 {
+        content.push_str(if i % 3 == 0 { "NeeDle aaaaa needle\n" } else { "nothing here\n" });
+    }
+    content.push_str("aaa");
+    let path = config.file("count.log", &content);
+
+    let count = |pattern: &str, use_regex, case_sensitive| {
+        let result = count_pattern_occurrences(path.clone(), pattern.to_string(), use_regex, case_sensitive);
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.total_lines, 50_002);
+        result.count
+    };
+    let tagged = 50_000 / 3 + 1;
+    assert_eq!(count("needle", false, true), 1 + tagged);
+    assert_eq!(count("needle", false, false), 1 + 2 * tagged);
+    // Non-overlapping: two in each "aaaaa", one in the trailing "aaa"
+    assert_eq!(count("aa", false, true), 2 * tagged + 1);
+    assert_eq!(count("n.{4}e", true, false), 1 + 2 * tagged);
+    assert_eq!(count("^NeeDle", true, true), tagged);
+    assert!(!count_pattern_occurrences(path.clone(), "(".to_string(), true, true).success);
+}
+
+#[test]
+fn re_adding_a_recent_file_moves_it_to_the_front() {
+    let config = TempConfigDir::new();
+    let first = config.file("first.log", "a\n");
+    let second = config.file("second.log", "b\n");
+
+    with_app_state(|state| {
+        assert!(add_recent_file(state.clone(), first.clone()));
+        assert!(add_recent_file(state.clone(), second.clone()));
+        assert!(add_recent_file(state, first.clone()));
+    });
+
+    let stored = config.recent_json();
+    assert_eq!(stored.len(), 2);
+    assert_eq!(stored[0]["path"], first.as_str());
+    assert_eq!(stored[0]["openCount"], 2);
+    assert_eq!(stored[1]["path"], second.as_str());
+}
+
+#[test]
+fn recent_files_since_tracks_versions_per_app() {
+    let config = TempConfigDir::new();
+    let first = config.file("first.log", "a\n");
+    let second = config.file("second.log", "b\n");
+
+    with_app_state(|state| {
+        assert!(add_recent_file(state.clone(), first.clone()));
+        let base = get_recent_files_since(state.clone(), 0).new_version;
+        assert_eq!(base, 1);
+        assert!(add_recent_file(state.clone(), second.clone()));
+
+        let delta = get_recent_files_since(state, base);
+        assert!(!delta.reset);
+        assert_eq!(delta.new_version, 2);
+        assert_eq!(delta.added.iter().map(|f| f.path.as_str()).collect::<Vec<_>>(), [second.as_str()]);
+        assert!(delta.removed.is_empty());
+    });
+
+    // A fresh app starts its own history rather than sharing the first one's
+    with_app_state(|state| {
+        let delta = get_recent_files_since(state, 1);
+        assert!(delta.reset);
+        assert_eq!(delta.new_version, 0);
+        assert_eq!(delta.added.len(), 2);
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn detect_log_rotation_notices_a_replaced_file() {
+    let config = TempConfigDir::new();
+    let path = config.file("app.log", "old contents\n");
+
+    with_app_state(|state| {
+        let first = detect_log_rotation(state.clone(), path.clone(), 0);
+        assert_eq!(first.inode_changed, None);
+        assert!(!first.rotated);
+
+        // Rotation by rename + create, with the new file no smaller than before
+        fs::rename(&path, config.path().join("app.log.1")).unwrap();
+        fs::write(&path, "new, longer contents\n").unwrap();
+        let rotated = detect_log_rotation(state.clone(), path.clone(), 13);
+        assert_eq!(rotated.inode_changed, Some(true));
+        assert!(rotated.rotated);
+
+        let again = detect_log_rotation(state, path.clone(), rotated.new_size);
+        assert_eq!(again.inode_changed, Some(false));
+    });
+}
+
+#[test]
+fn session_save_rotates_slots() {
+    let config = TempConfigDir::new();
+    let session = |n: i64| SessionState {
+        file_path: format!("/{}.log", n),
+        byte_offset: 0,
+        search_query: None,
+        pinned_files: vec![],
+        opened_at: n,
+    };
+
+    for n in 1..=3 {
+        assert!(session_save(session(n)));
+    }
+
+    let opened: Vec<i64> = (0..=MAX_SESSION_SLOTS).filter_map(|slot| session_restore(Some(slot)).map(|s| s.opened_at)).collect();
+    assert_eq!(opened, [3, 2, 1]);
+    // Only the three slots; no temp files left behind
+    let sessions_dir = get_sessions_dir().unwrap();
+    assert!(sessions_dir.starts_with(config.path()));
+    assert_eq!(fs::read_dir(sessions_dir).unwrap().count(), 3);
+}
+
+#[test]
+fn search_history_is_written_on_flush() {
+    let config = TempConfigDir::new();
+    let path = config.file("app.log", "one\ntwo\n");
+    let history_path = config.path().join("search_history.json");
+
+    with_app_state(|state| {
+        search_file_for_line(state.clone(), path.clone(), "one".to_string(), 0, false, None);
+        search_file_for_line(state.clone(), path.clone(), "two".to_string(), 0, false, None);
+        assert!(!history_path.exists());
+
+        assert!(flush_search_history(&state));
+        assert_eq!(load_json_list::<SearchHistoryEntry>(&history_path).len(), 2);
+
+        // Clearing writes straight away, leaving nothing for the next flush
+        assert!(clear_search_history(state.clone(), Some(path.clone())));
+        assert!(load_json_list::<SearchHistoryEntry>(&history_path).is_empty());
+        fs::remove_file(&history_path).unwrap();
+        assert!(flush_search_history(&state));
+        assert!(!history_path.exists());
+    });
+}
@@ -1,6 +1,131 @@
 mod commands;
+mod state;
 
-use commands::{read_file, get_recent_files, add_recent_file, remove_recent_file, clear_recent_files, export_file, search_file_for_line};
+use commands::{
+    read_file,
+    get_recent_files,
+    add_recent_file,
+    remove_recent_file,
+    clear_recent_files,
+    export_file,
+    check_write_permission_at_path,
+    search_file_for_line,
+    watch_file,
+    unwatch_file,
+    get_watch_stats,
+    reset_watch_stats,
+    list_active_watches,
+    get_file_change_velocity,
+    tag_recent_file,
+    remove_tag_from_recent_file,
+    get_recent_files_by_tag,
+    clear_recent_files_matching,
+    save_search,
+    get_saved_searches,
+    delete_saved_search,
+    apply_saved_search,
+    get_recent_files_since,
+    get_search_history,
+    clear_search_history,
+    detect_log_rotation,
+    get_file_tail_raw,
+    get_file_change_since,
+    read_file_reverse,
+    merge_log_files,
+    parse_json_log,
+    normalize_path,
+    get_log_level_counts,
+    session_save,
+    session_restore,
+    get_sessions,
+    set_recent_file_pinned,
+    read_log_sequence,
+    diagnostics,
+    export_filtered_lines,
+    rename_recent_file,
+    clear_alias,
+    count_pattern_occurrences,
+    check_file_permissions,
+    watch_directory,
+    unwatch_directory,
+    get_watched_directories,
+    get_file_tail_with_context,
+    export_recent_files,
+    import_recent_files,
+    read_file_at_line,
+    get_config,
+    parse_apache_access_log,
+    filter_by_time_range,
+    get_top_messages,
+    diff_files,
+    validate_recent_json,
+    repair_recent_json,
+    read_file_sample,
+    extract_unique_lines,
+    get_config_directory,
+    get_file_inode,
+    read_file_async,
+    search_file_async,
+    search_recent_files,
+    backup_config,
+    restore_config,
+    list_config_backups,
+    backup_recent_files,
+    restore_recent_files,
+    read_syslog,
+    read_file_range,
+    parse_nginx_access_log,
+    create_bookmark,
+    get_bookmarks,
+    delete_bookmark,
+    clear_bookmarks,
+    read_bookmark,
+    estimate_line_count,
+    get_file_permissions_string,
+    get_file_tail_offset,
+    move_recent_files_to_group,
+    get_recent_file_groups,
+    compress_export,
+    decompress_file,
+    search_across_recent_files,
+    parse_csv_log,
+    get_timeline_data,
+    get_error_rate,
+    read_file_with_highlights,
+    resolve_symlinks_in_recent_files,
+    write_annotation,
+    get_annotations,
+    delete_annotation,
+    export_as_html,
+    lock_config,
+    unlock_config,
+    set_config,
+    get_file_character_stats,
+    get_file_growth_rate,
+    apply_log_transform,
+    get_inotify_stats,
+    read_pipe,
+    group_recent_files_by_directory,
+    set_recent_file_notes,
+    get_recent_file_notes,
+    read_file_with_line_numbers,
+    get_disk_space,
+    find_log_errors_near_timestamp,
+    read_file_with_options,
+    apply_regex_filter_export,
+    start_file_session,
+    end_file_session,
+    get_total_open_duration,
+    get_recent_files_statistics,
+    start_tail_follow,
+    stop_tail_follow,
+    compare_log_timestamps,
+    get_log_format_hints,
+    run_lua_filter,
+    flush_search_history,
+};
+use state::AppState;
+use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -9,6 +134,7 @@ pub fn run() {
             .level(log::LevelFilter::Info)
             .build())
         .plugin(tauri_plugin_dialog::init())
+        .manage(AppState::new())
         .invoke_handler(tauri::generate_handler![
             read_file,
             get_recent_files,
@@ -16,8 +142,127 @@ pub fn run() {
             remove_recent_file,
             clear_recent_files,
             export_file,
-            search_file_for_line
+            check_write_permission_at_path,
+            search_file_for_line,
+            watch_file,
+            unwatch_file,
+            get_watch_stats,
+            reset_watch_stats,
+            list_active_watches,
+            get_file_change_velocity,
+            tag_recent_file,
+            remove_tag_from_recent_file,
+            get_recent_files_by_tag,
+            clear_recent_files_matching,
+            save_search,
+            get_saved_searches,
+            delete_saved_search,
+            apply_saved_search,
+            get_recent_files_since,
+            get_search_history,
+            clear_search_history,
+            detect_log_rotation,
+            get_file_tail_raw,
+            get_file_change_since,
+            read_file_reverse,
+            merge_log_files,
+            parse_json_log,
+            normalize_path,
+            get_log_level_counts,
+            session_save,
+            session_restore,
+            get_sessions,
+            set_recent_file_pinned,
+            read_log_sequence,
+            diagnostics,
+            export_filtered_lines,
+            rename_recent_file,
+            clear_alias,
+            count_pattern_occurrences,
+            check_file_permissions,
+            watch_directory,
+            unwatch_directory,
+            get_watched_directories,
+            get_file_tail_with_context,
+            export_recent_files,
+            import_recent_files,
+            read_file_at_line,
+            get_config,
+            parse_apache_access_log,
+            filter_by_time_range,
+            get_top_messages,
+            diff_files,
+            validate_recent_json,
+            repair_recent_json,
+            read_file_sample,
+            extract_unique_lines,
+            get_config_directory,
+            get_file_inode,
+            read_file_async,
+            search_file_async,
+            search_recent_files,
+            backup_config,
+            restore_config,
+            list_config_backups,
+            backup_recent_files,
+            restore_recent_files,
+            read_syslog,
+            read_file_range,
+            parse_nginx_access_log,
+            create_bookmark,
+            get_bookmarks,
+            delete_bookmark,
+            clear_bookmarks,
+            read_bookmark,
+            estimate_line_count,
+            get_file_permissions_string,
+            get_file_tail_offset,
+            move_recent_files_to_group,
+            get_recent_file_groups,
+            compress_export,
+            decompress_file,
+            search_across_recent_files,
+            parse_csv_log,
+            get_timeline_data,
+            get_error_rate,
+            read_file_with_highlights,
+            resolve_symlinks_in_recent_files,
+            write_annotation,
+            get_annotations,
+            delete_annotation,
+            export_as_html,
+            lock_config,
+            unlock_config,
+            set_config,
+            get_file_character_stats,
+            get_file_growth_rate,
+            apply_log_transform,
+            get_inotify_stats,
+            read_pipe,
+            group_recent_files_by_directory,
+            set_recent_file_notes,
+            get_recent_file_notes,
+            read_file_with_line_numbers,
+            get_disk_space,
+            find_log_errors_near_timestamp,
+            read_file_with_options,
+            apply_regex_filter_export,
+            start_file_session,
+            end_file_session,
+            get_total_open_duration,
+            get_recent_files_statistics,
+            start_tail_follow,
+            stop_tail_follow,
+            compare_log_timestamps,
+            get_log_format_hints,
+            run_lua_filter
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Searches only update the in-memory history; write it out once on the way out
+            if let tauri::RunEvent::Exit = event {
+                flush_search_history(&app.state::<AppState>());
+            }
+        });
 }
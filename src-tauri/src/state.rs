@@ -0,0 +1,344 @@
+use lru::LruCache;
+use notify::RecommendedWatcher;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Mutex, RwLock};
+
+use crate::commands::{
+    get_config_path, get_search_history_path, load_json_list, ActiveWatcher, RecentHistory,
+    SearchHistoryEntry, SessionTotals, TailFollower,
+};
+
+// Line indexes kept in memory when config.json doesn't say otherwise
+const DEFAULT_LINE_INDEX_CACHE_SIZE: usize = 10;
+
+// Read buffer size used while building a line index
+const INDEX_BUFFER_SIZE: usize = 256 * 1024;
+
+/// User settings from config.json in the config directory; missing fields take their defaults
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Config {
+    pub line_index_cache_size: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            line_index_cache_size: DEFAULT_LINE_INDEX_CACHE_SIZE,
+        }
+    }
+}
+
+impl Config {
+    /// Load config.json, falling back to defaults if it is missing or invalid
+    pub fn load() -> Self {
+        get_config_path()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|c| serde_json::from_str(&c).ok())
+            .unwrap_or_default()
+    }
+
+    /// Line index cache capacity; at least one entry is always kept
+    pub fn line_index_capacity(&self) -> NonZeroUsize {
+        NonZeroUsize::new(self.line_index_cache_size).unwrap_or(NonZeroUsize::MIN)
+    }
+}
+
+// Lines between the offsets a LineIndex keeps; lines in between are found by scanning
+// forward from the nearest kept offset, so memory stays small for huge files
+const LINE_INDEX_STRIDE: usize = 1024;
+
+/// Byte offset of the start of every LINE_INDEX_STRIDE-th line in a file, for fast
+/// line-range reads
+pub struct LineIndex {
+    /// checkpoints[i] is where line i * LINE_INDEX_STRIDE (0-indexed) starts
+    checkpoints: Vec<u64>,
+    line_count: usize,
+    /// File size and mtime when the index was built, to detect stale entries
+    pub size: u64,
+    pub mtime: Option<i64>,
+}
+
+impl LineIndex {
+    /// Scan a file once, recording where every LINE_INDEX_STRIDE-th line starts
+    pub fn build(path: &Path, mtime: Option<i64>) -> std::io::Result<Self> {
+        let mut index = LineIndex {
+            checkpoints: Vec::new(),
+            line_count: 0,
+            size: 0,
+            mtime,
+        };
+        index.extend(path, mtime)?;
+        Ok(index)
+    }
+
+    /// Scan only the bytes appended since the index was last updated
+    /// Assumes the already indexed part of the file is unchanged
+    pub fn extend(&mut self, path: &Path, mtime: Option<i64>) -> std::io::Result<()> {
+        let mut file = File::open(path)?;
+
+        // The old end of file starts a new line only if it ended with a newline
+        let mut at_line_start = true;
+        if self.size > 0 {
+            let mut last = [0u8; 1];
+            file.seek(SeekFrom::Start(self.size - 1))?;
+            file.read_exact(&mut last)?;
+            at_line_start = last[0] == b'\n';
+        }
+
+        let mut reader = BufReader::with_capacity(INDEX_BUFFER_SIZE, file);
+        let mut pos = self.size;
+
+        loop {
+            let chunk = reader.fill_buf()?;
+            if chunk.is_empty() {
+                break;
+            }
+
+            if at_line_start {
+                self.add_line(pos);
+            }
+            let chunk_len = chunk.len();
+            for i in memchr::memchr_iter(b'\n', chunk) {
+                if i + 1 < chunk_len {
+                    self.add_line(pos + i as u64 + 1);
+                }
+            }
+            at_line_start = chunk[chunk_len - 1] == b'\n';
+
+            pos += chunk_len as u64;
+            reader.consume(chunk_len);
+        }
+
+        self.size = pos;
+        self.mtime = mtime;
+        Ok(())
+    }
+
+    /// Count a line starting at offset, keeping it if it falls on a checkpoint
+    fn add_line(&mut self, offset: u64) {
+        if self.line_count % LINE_INDEX_STRIDE == 0 {
+            self.checkpoints.push(offset);
+        }
+        self.line_count += 1;
+    }
+
+    /// Number of lines in the indexed file
+    pub fn line_count(&self) -> usize {
+        self.line_count
+    }
+
+    /// Byte range covering lines first..=last (0-indexed, clamped to the file)
+    /// None if first is past the last line
+    pub fn byte_range(&self, path: &Path, first: usize, last: usize) -> std::io::Result<Option<(u64, u64)>> {
+        if first >= self.line_count {
+            return Ok(None);
+        }
+
+        let checkpoint = first / LINE_INDEX_STRIDE;
+        let checkpoint_offset = self.checkpoints[checkpoint];
+        let mut file = File::open(path)?;
+        file.seek(SeekFrom::Start(checkpoint_offset))?;
+        let mut reader = BufReader::new(file.take(self.size - checkpoint_offset));
+
+        let start = checkpoint_offset + skip_lines(&mut reader, first - checkpoint * LINE_INDEX_STRIDE)?;
+        let end = if last + 1 >= self.line_count {
+            self.size
+        } else {
+            start + skip_lines(&mut reader, (last + 1).saturating_sub(first))?
+        };
+        Ok(Some((start, end)))
+    }
+
+    /// 0-indexed line containing offset
+    pub fn line_at_offset(&self, path: &Path, offset: u64) -> std::io::Result<usize> {
+        let checkpoint = self.checkpoints.partition_point(|&o| o <= offset).saturating_sub(1);
+        let checkpoint_offset = match self.checkpoints.get(checkpoint) {
+            Some(&o) => o,
+            None => return Ok(0),
+        };
+
+        let mut file = File::open(path)?;
+        file.seek(SeekFrom::Start(checkpoint_offset))?;
+        let mut reader = BufReader::new(file.take(offset.min(self.size) - checkpoint_offset));
+        let mut newlines = 0;
+        loop {
+            let chunk = reader.fill_buf()?;
+            if chunk.is_empty() {
+                break;
+            }
+            newlines += memchr::memchr_iter(b'\n', chunk).count();
+            let consumed = chunk.len();
+            reader.consume(consumed);
+        }
+        Ok(checkpoint * LINE_INDEX_STRIDE + newlines)
+    }
+}
+
+/// Consume lines lines from reader, returning the bytes skipped
+/// Stops early at end of input
+fn skip_lines(reader: &mut impl BufRead, lines: usize) -> std::io::Result<u64> {
+    let mut remaining = lines;
+    let mut skipped = 0;
+    while remaining > 0 {
+        let chunk = reader.fill_buf()?;
+        if chunk.is_empty() {
+            break;
+        }
+        let used = match memchr::memchr_iter(b'\n', chunk).nth(remaining - 1) {
+            Some(i) => {
+                remaining = 0;
+                i + 1
+            }
+            None => {
+                remaining -= memchr::memchr_iter(b'\n', chunk).count();
+                chunk.len()
+            }
+        };
+        skipped += used as u64;
+        reader.consume(used);
+    }
+    Ok(skipped)
+}
+
+/// State shared by all commands, registered with Tauri via manage()
+pub struct AppState {
+    pub config: RwLock<Config>,
+    /// Active file watchers, keyed by watched file path
+    pub watchers: Mutex<HashMap<String, ActiveWatcher>>,
+    /// Active directory watchers, keyed by directory path
+    pub directory_watchers: Mutex<HashMap<String, RecommendedWatcher>>,
+    /// Line indexes for recently read files, keyed by path
+    pub line_index_cache: Mutex<LruCache<String, LineIndex>>,
+    /// Search history (oldest first), written to search_history.json when cleared and on exit
+    pub search_history: Mutex<Vec<SearchHistoryEntry>>,
+    /// Set while search_history has changes not yet written to search_history.json
+    pub search_history_dirty: AtomicBool,
+    /// Held across read-modify-write cycles of recent.json so concurrent updates aren't lost
+    pub recent_files_lock: Mutex<()>,
+    /// Recent snapshots of the recent files list, for get_recent_files_since
+    pub recent_history: Mutex<RecentHistory>,
+    /// The config directory's config.lock while lock_config holds its advisory lock
+    pub config_lock: Mutex<Option<File>>,
+    /// Start times (Unix millis) of file sessions begun with start_file_session, keyed by path
+    pub open_sessions: Mutex<HashMap<String, i64>>,
+    /// Per-path totals parsed from sessions_log.json, reused while the log is unchanged
+    pub session_totals: Mutex<Option<SessionTotals>>,
+    /// Files followed with start_tail_follow, keyed by path
+    pub tail_followers: Mutex<HashMap<String, TailFollower>>,
+    /// Last inode seen by detect_log_rotation, keyed by path
+    pub known_inodes: Mutex<HashMap<String, u64>>,
+}
+
+impl AppState {
+    pub fn new() -> Self {
+        let config = Config::load();
+        let cache_size = config.line_index_capacity();
+        let search_history = get_search_history_path()
+            .map(|p| load_json_list(&p))
+            .unwrap_or_default();
+
+        AppState {
+            config: RwLock::new(config),
+            watchers: Mutex::new(HashMap::new()),
+            directory_watchers: Mutex::new(HashMap::new()),
+            line_index_cache: Mutex::new(LruCache::new(cache_size)),
+            search_history: Mutex::new(search_history),
+            search_history_dirty: AtomicBool::new(false),
+            recent_files_lock: Mutex::new(()),
+            recent_history: Mutex::new(RecentHistory::default()),
+            config_lock: Mutex::new(None),
+            open_sessions: Mutex::new(HashMap::new()),
+            session_totals: Mutex::new(None),
+            tail_followers: Mutex::new(HashMap::new()),
+            known_inodes: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn numbered_file(lines: usize) -> tempfile::NamedTempFile {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        for i in 0..lines {
+            writeln!(file, "line {}", i).unwrap();
+        }
+        file.flush().unwrap();
+        file
+    }
+
+    fn read_range(path: &Path, (start, end): (u64, u64)) -> String {
+        let bytes = std::fs::read(path).unwrap();
+        String::from_utf8(bytes[start as usize..end as usize].to_vec()).unwrap()
+    }
+
+    #[test]
+    fn line_index_keeps_only_checkpoints() {
+        let file = numbered_file(LINE_INDEX_STRIDE * 3 + 5);
+        let index = LineIndex::build(file.path(), None).unwrap();
+        assert_eq!(index.line_count(), LINE_INDEX_STRIDE * 3 + 5);
+        assert_eq!(index.checkpoints.len(), 4);
+    }
+
+    #[test]
+    fn line_index_byte_range_scans_from_checkpoint() {
+        let file = numbered_file(LINE_INDEX_STRIDE * 2 + 10);
+        let index = LineIndex::build(file.path(), None).unwrap();
+
+        for first in [0, 1, LINE_INDEX_STRIDE - 1, LINE_INDEX_STRIDE, LINE_INDEX_STRIDE + 7] {
+            let range = index.byte_range(file.path(), first, first + 2).unwrap().unwrap();
+            let expected = format!("line {}\nline {}\nline {}\n", first, first + 1, first + 2);
+            assert_eq!(read_range(file.path(), range), expected);
+        }
+
+        let last = LINE_INDEX_STRIDE * 2 + 9;
+        let range = index.byte_range(file.path(), last, last + 100).unwrap().unwrap();
+        assert_eq!(read_range(file.path(), range), format!("line {}\n", last));
+        assert_eq!(index.byte_range(file.path(), last + 1, last + 1).unwrap(), None);
+    }
+
+    #[test]
+    fn line_index_line_at_offset() {
+        let file = numbered_file(LINE_INDEX_STRIDE * 2);
+        let index = LineIndex::build(file.path(), None).unwrap();
+
+        for line in [0, 5, LINE_INDEX_STRIDE, LINE_INDEX_STRIDE + 300] {
+            let (start, end) = index.byte_range(file.path(), line, line).unwrap().unwrap();
+            assert_eq!(index.line_at_offset(file.path(), start).unwrap(), line);
+            assert_eq!(index.line_at_offset(file.path(), end - 1).unwrap(), line);
+        }
+    }
+
+    #[test]
+    fn line_index_extend_continues_partial_line() {
+        let mut file = numbered_file(LINE_INDEX_STRIDE);
+        write!(file, "partial").unwrap();
+        file.flush().unwrap();
+        let mut index = LineIndex::build(file.path(), None).unwrap();
+        assert_eq!(index.line_count(), LINE_INDEX_STRIDE + 1);
+
+        writeln!(file, " end").unwrap();
+        writeln!(file, "next").unwrap();
+        file.flush().unwrap();
+        index.extend(file.path(), None).unwrap();
+
+        assert_eq!(index.line_count(), LINE_INDEX_STRIDE + 2);
+        let range = index.byte_range(file.path(), LINE_INDEX_STRIDE, LINE_INDEX_STRIDE + 1).unwrap().unwrap();
+        assert_eq!(read_range(file.path(), range), "partial end\nnext\n");
+    }
+}
@@ -6,7 +6,7 @@
  */
 
 import { invoke } from '@tauri-apps/api/core';
-import type { FileResult, RecentFile, SearchLineResult } from './types';
+import type { ExportOptions, ExportResult, FileResult, PaginatedRecentFiles, ReadFileOptions, RecentFile, SearchLineResult } from './types';
 
 /**
  * Check if running in Tauri context
@@ -32,22 +32,79 @@ export async function waitForConnection(_timeoutMs: number = 5000): Promise<bool
  *
  * @param path - Full path to the file
  * @param offset - Byte offset to start reading from (0 for full file, >0 for differential/polling)
+ * @param stripPrefixRegex - Optional regex; the matched prefix is removed from each line
+ * @param normalizeCrlf - Convert CRLF line endings to LF in the returned content
+ * @param expectedInode - Inode seen on the previous read; a different inode means the file was replaced
  * @returns FileResult with content, size info, and success status
  *
  * For initial file load, use offset=0 to read the entire file.
  * For polling updates, pass the previous file size as offset to only get new bytes.
  */
-export async function readFile(path: string, offset: number = 0): Promise<FileResult> {
+export async function readFile(
+  path: string,
+  offset: number = 0,
+  stripPrefixRegex?: string,
+  normalizeCrlf: boolean = false,
+  expectedInode?: number
+): Promise<FileResult> {
   if (!isTauri()) {
-    return { success: false, error: 'Not running in Tauri context' };
+    return {
+      success: false,
+      read_from_offset: 0,
+      next_offset: 0,
+      error: 'Not running in Tauri context',
+    };
   }
 
   try {
-    const result = await invoke<FileResult>('read_file', { path, offset });
+    const result = await invoke<FileResult>('read_file', {
+      path,
+      offset,
+      stripPrefixRegex,
+      normalizeCrlf,
+      expectedInode,
+    });
     return result;
   } catch (err) {
     return {
       success: false,
+      read_from_offset: 0,
+      next_offset: 0,
+      error: err instanceof Error ? err.message : String(err),
+    };
+  }
+}
+
+/**
+ * Read a file like readFile, with the optional settings grouped in one object
+ *
+ * @param path - Full path to the file
+ * @param offset - Byte offset to start reading from (0 for full file, >0 for differential/polling)
+ * @param options - Optional settings; relativeTo makes display_name relative to that directory
+ * @returns FileResult with content, size info, and success status
+ */
+export async function readFileWithOptions(
+  path: string,
+  offset: number = 0,
+  options?: ReadFileOptions
+): Promise<FileResult> {
+  if (!isTauri()) {
+    return {
+      success: false,
+      read_from_offset: 0,
+      next_offset: 0,
+      error: 'Not running in Tauri context',
+    };
+  }
+
+  try {
+    const result = await invoke<FileResult>('read_file_with_options', { path, offset, options });
+    return result;
+  } catch (err) {
+    return {
+      success: false,
+      read_from_offset: 0,
+      next_offset: 0,
       error: err instanceof Error ? err.message : String(err),
     };
   }
@@ -56,14 +113,16 @@ export async function readFile(path: string, offset: number = 0): Promise<FileRe
 /**
  * Get the list of recently opened files from ~/.mocha/recent.json
  *
+ * @param page - 0-indexed page to fetch (defaults to the first page)
+ * @param pageSize - Entries per page (defaults to the whole list)
  * @returns Array of RecentFile objects, sorted by lastOpened (newest first)
  */
-export async function getRecentFiles(): Promise<RecentFile[]> {
+export async function getRecentFiles(page?: number, pageSize?: number): Promise<RecentFile[]> {
   if (!isTauri()) return [];
 
   try {
-    const files = await invoke<RecentFile[]>('get_recent_files');
-    return Array.isArray(files) ? files : [];
+    const result = await invoke<PaginatedRecentFiles>('get_recent_files', { page, pageSize });
+    return Array.isArray(result?.files) ? result.files : [];
   } catch (err) {
     console.error('getRecentFiles error:', err);
     return [];
@@ -108,12 +167,14 @@ export async function removeRecentFile(path: string): Promise<void> {
 
 /**
  * Clear the recent files list in ~/.mocha/recent.json
+ *
+ * @param keepPinned - Keep pinned entries instead of clearing everything
  */
-export async function clearRecentFiles(): Promise<void> {
+export async function clearRecentFiles(keepPinned: boolean = false): Promise<void> {
   if (!isTauri()) return;
 
   try {
-    await invoke('clear_recent_files');
+    await invoke('clear_recent_files', { keepPinned });
   } catch (err) {
     console.error('clearRecentFiles error:', err);
   }
@@ -124,14 +185,32 @@ export async function clearRecentFiles(): Promise<void> {
  *
  * @param path - Full path to the file to write
  * @param content - Content to write to the file
+ * @param createParents - Create missing parent directories before writing (default: true)
+ * @param dryRun - Only validate that the destination is writable
+ * @param options - Extra output options, e.g. a UTF-8 BOM for Excel
  * @returns true if successful, false otherwise
  */
-export async function exportFile(path: string, content: string): Promise<boolean> {
+export async function exportFile(
+  path: string,
+  content: string,
+  createParents?: boolean,
+  dryRun: boolean = false,
+  options?: ExportOptions
+): Promise<boolean> {
   if (!isTauri()) return false;
 
   try {
-    const result = await invoke<boolean>('export_file', { path, content });
-    return result;
+    const result = await invoke<ExportResult>('export_file', {
+      path,
+      content,
+      createParents,
+      dryRun,
+      options,
+    });
+    if (!result.success) {
+      console.error('exportFile error:', result.error);
+    }
+    return result.success;
   } catch (err) {
     console.error('exportFile error:', err);
     return false;
@@ -145,12 +224,16 @@ export async function exportFile(path: string, content: string): Promise<boolean
  * @param path - Full path to the file to search
  * @param searchLine - The exact line content to search for
  * @param contextLines - Number of lines to include before and after the match (default: 500)
+ * @param benchmark - Also return scan timing and throughput for diagnostics
+ * @param lineRange - Only match lines in [start, end] (1-indexed), e.g. [4900, 5100]
  * @returns SearchLineResult with context content and line number if found
  */
 export async function searchFileForLine(
   path: string,
   searchLine: string,
-  contextLines: number = 500
+  contextLines: number = 500,
+  benchmark: boolean = false,
+  lineRange?: [number, number]
 ): Promise<SearchLineResult> {
   if (!isTauri()) {
     return { success: false, error: 'Not running in Tauri context' };
@@ -161,6 +244,8 @@ export async function searchFileForLine(
       path,
       searchLine,
       contextLines,
+      benchmark,
+      lineRange,
     });
     return result;
   } catch (err) {
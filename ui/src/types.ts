@@ -135,11 +135,31 @@ export interface OpenedFileWithLogs extends OpenedFile {
  */
 export interface RecentFile {
   path: string; // Full file path
-  name: string; // Filename only
+  name: string; // Alias if set, otherwise filename only
   lastOpened: number; // Unix timestamp in milliseconds
   mtime?: number; // File modification time (Unix millis)
+  mtimeIso?: string; // mtime as an RFC 3339 string in UTC
   size?: number; // File size in bytes
   exists: boolean; // Whether file exists on disk
+  tags?: string[]; // User-assigned tags for grouping
+  openCount?: number; // Times the file has been opened
+  isPinned?: boolean; // Pinned entries survive "clear, keep pinned"
+  alias?: string; // User-set display name (name already reflects it)
+  metadataFresh?: boolean; // False if mtime/size are stale (metadata lookup timed out)
+  group?: string; // Group the file was moved into, if any
+  notes?: string; // User note on why the file matters
+  totalOpenSeconds?: number; // Time spent in recorded file sessions
+}
+
+/**
+ * One page of the recent files list from getRecentFiles
+ */
+export interface PaginatedRecentFiles {
+  files: RecentFile[];
+  total: number; // Entries in the whole list
+  page: number; // 0-indexed page number
+  pageSize: number;
+  hasMore: boolean; // True if later pages exist
 }
 
 /**
@@ -150,11 +170,33 @@ export interface FileResult {
   content?: string; // File contents (new bytes only if offset > 0)
   path?: string; // Full file path
   name?: string; // Filename only
+  display_name?: string; // Filename, or path relative to a base when names are ambiguous
   size?: number; // Current file size in bytes
-  prevSize?: number; // Offset that was passed in
+  read_from_offset: number; // Byte offset this read started from
+  next_offset: number; // Offset to pass to the next polling read
   mtime?: number; // File modification time (Unix millis)
+  mtime_iso?: string; // mtime as an RFC 3339 string in UTC
   truncated?: boolean; // True if file was truncated/replaced
-  error?: string; // Error message if failed
+  prefix_pattern_used?: string; // Prefix regex applied when stripping was requested
+  lines_with_stripped_prefix?: number; // Lines that had a prefix stripped
+  lines_returned?: number; // Lines in content (line-based tail reads only)
+  start_line?: number; // 1-indexed line number of the first returned line
+  end_line?: number; // 1-indexed line number of the last returned line
+  total_lines?: number; // Lines in the whole file (line-addressed reads only)
+  anchor_line?: number; // 0-indexed line in content holding the requested anchor (bookmarks)
+  replacement_char_count?: number; // U+FFFD characters, mostly from invalid UTF-8
+  error?: string; // Error message if failed, or an encoding warning on success
+  error_code?: 'FileDisappearedBetweenStatAndOpen'; // Set for failures the UI handles specially
+}
+
+/**
+ * Optional settings for readFileWithOptions; see readFile for each
+ */
+export interface ReadFileOptions {
+  stripPrefixRegex?: string; // The matched prefix is removed from each line
+  normalizeCrlf?: boolean; // Convert CRLF line endings to LF
+  expectedInode?: number; // Inode seen on the previous read
+  relativeTo?: string; // Base directory for display_name when file names are ambiguous
 }
 
 /**
@@ -165,10 +207,34 @@ export interface SearchLineResult {
   success: boolean;
   content?: string; // Context lines around the found line
   lineNumber?: number; // 1-indexed line number where match was found
-  totalLines?: number; // Total lines in the file
+  totalLines?: number; // Total lines in the file; unset if a lineRange search stopped early
+  searchTimeMs?: number; // Scan wall-clock time (benchmark mode only)
+  bytesScanned?: number; // Bytes read during the scan (benchmark mode only)
+  throughputMbPerSec?: number; // Scan throughput (benchmark mode only)
+  byteOffset?: number; // Byte offset where the matched line starts
+  contextStartOffset?: number; // Byte offset where the first context line starts
+  rangeBounded?: boolean; // True if only a line range was searched
   error?: string; // Error message if failed
 }
 
+/**
+ * Result from exportFile Tauri command
+ */
+export interface ExportResult {
+  success: boolean;
+  createdDirs: boolean; // True if missing parent directories were created
+  bytesWritten: number; // 0 for dry runs
+  bomWritten: boolean; // True if a UTF-8 BOM was written before the content
+  error?: string; // Error message if failed
+}
+
+/**
+ * Options for exportFile Tauri command
+ */
+export interface ExportOptions {
+  includeBom?: boolean; // Start the file with a UTF-8 BOM for Excel/PowerShell
+}
+
 // ============================================================================
 // Filter Types
 // ============================================================================